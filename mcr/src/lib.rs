@@ -6,8 +6,8 @@
 //! https://github.com/dtolnay/syn/blob/master/examples/lazy-static/lazy-static/src/lib.rs
 
 #![cfg_attr(debug_assertions, allow(dead_code, unused_imports, unused_variables))]
-#![feature(proc_macro_diagnostic)]
-#![feature(extend_one)]
+// Functions return explicitly.
+#![allow(clippy::needless_return)]
 
 use proc_macro::TokenStream;
 use proc_macro2::{Group, Span, TokenTree};
@@ -114,7 +114,7 @@ impl Iterator for BlkItr {
       Itm{
         shf_dir: Dir::Zro,
         shf_len: 0,
-        lne_bit_len: lne_bit_len,
+        lne_bit_len,
         lne_bit_sum: lne_bit_len,
         blk_bit_sum: lne_bit_len * ELM_PER_SMD,
       }
@@ -125,7 +125,7 @@ impl Iterator for BlkItr {
       Itm{
         shf_dir: Dir::Fwd,
         shf_len: self.prv.lne_bit_sum,
-        lne_bit_len: lne_bit_len,
+        lne_bit_len,
         lne_bit_sum: self.prv.lne_bit_sum + self.elm_bit_len,
         blk_bit_sum: self.prv.blk_bit_sum + (lne_bit_len * ELM_PER_SMD),
       }
@@ -135,7 +135,7 @@ impl Iterator for BlkItr {
       Itm{
         shf_dir: Dir::FwdPrt,
         shf_len: self.prv.lne_bit_sum,
-        lne_bit_len: lne_bit_len,
+        lne_bit_len,
         lne_bit_sum: self.bit_per_lne,
         blk_bit_sum: self.prv.blk_bit_sum + (lne_bit_len * ELM_PER_SMD),
       }
@@ -147,7 +147,7 @@ impl Iterator for BlkItr {
         Itm{
           shf_dir: Dir::Zro,
           shf_len: 0,
-          lne_bit_len: lne_bit_len,
+          lne_bit_len,
           lne_bit_sum: lne_bit_len,
           blk_bit_sum: self.prv.blk_bit_sum + (lne_bit_len * ELM_PER_SMD),
         }
@@ -157,7 +157,7 @@ impl Iterator for BlkItr {
         Itm{
           shf_dir: Dir::Bck,
           shf_len: self.prv.lne_bit_len,
-          lne_bit_len: lne_bit_len,
+          lne_bit_len,
           lne_bit_sum: lne_bit_len,
           blk_bit_sum: self.prv.blk_bit_sum + (lne_bit_len * ELM_PER_SMD),
         }
@@ -196,7 +196,7 @@ fn u32_blk_pck(elm_per_blk: usize, smd_per_blk: usize) -> proc_macro2::TokenStre
 
     // Arm 0
     // No packing or copying occurs at a bit-length of 0
-    gs_tree.extend_one(quote! {
+    gs_tree.extend(quote! {
       0u8 => {},
     });
 
@@ -227,7 +227,7 @@ fn u32_blk_pck(elm_per_blk: usize, smd_per_blk: usize) -> proc_macro2::TokenStre
             Itr::Fst => {
               // No shift for 1st SIMD lane
               // 1st SIMD vector starts with all first u32s filled
-              gs.extend_one(quote! {
+              gs.extend(quote! {
                 let mut prv = set1(fst as i32);
                 let mut cur = load(unp_ptr);
                 let mut smd_pck = sub(cur, prv);
@@ -239,7 +239,7 @@ fn u32_blk_pck(elm_per_blk: usize, smd_per_blk: usize) -> proc_macro2::TokenStre
               match itm.shf_dir {
                 Dir::Zro => {
                   // No shift
-                  gs.extend_one(quote! {
+                  gs.extend(quote! {
                     cur = load(unp_ptr.add(#unp_ptr_off));
                     smd_pck = sub(cur, prv);
                     prv = cur;
@@ -248,13 +248,13 @@ fn u32_blk_pck(elm_per_blk: usize, smd_per_blk: usize) -> proc_macro2::TokenStre
                 },
                 Dir::Bck => {
                   // Partial right shift
-                  gs.extend_one(quote! {
+                  gs.extend(quote! {
                     smd_pck = rht(dlt, #shf_lit);
                   });
                 },
                 Dir::Fwd => {
                   // Full left shift
-                  gs.extend_one(quote! {
+                  gs.extend(quote! {
                     cur = load(unp_ptr.add(#unp_ptr_off));
                     smd_pck = or(smd_pck, lft(sub(cur, prv), #shf_lit));
                     prv = cur;
@@ -263,7 +263,7 @@ fn u32_blk_pck(elm_per_blk: usize, smd_per_blk: usize) -> proc_macro2::TokenStre
                 },
                 Dir::FwdPrt => {
                   // Partial left shift
-                  gs.extend_one(quote! {
+                  gs.extend(quote! {
                     cur = load(unp_ptr.add(#unp_ptr_off));
                     let dlt = sub(cur, prv);
                     smd_pck = or(smd_pck, lft(dlt, #shf_lit));
@@ -275,7 +275,7 @@ fn u32_blk_pck(elm_per_blk: usize, smd_per_blk: usize) -> proc_macro2::TokenStre
 
               // Write fully packed SIMD vector
               if itm.lne_bit_sum == BIT_PER_LNE {
-                gs.extend_one(quote! {
+                gs.extend(quote! {
                   store(pck_ptr.add(#pck_ptr_off), smd_pck);
                 });
                 pck_ptr_off += 1;
@@ -287,7 +287,7 @@ fn u32_blk_pck(elm_per_blk: usize, smd_per_blk: usize) -> proc_macro2::TokenStre
               match itm.shf_dir {
                 Dir::Zro => {
                   // No shift
-                  gs.extend_one(quote! {
+                  gs.extend(quote! {
                     store(
                       pck_ptr.add(#pck_ptr_off),
                       sub(load(unp_ptr.add(#unp_ptr_off)), prv),
@@ -296,7 +296,7 @@ fn u32_blk_pck(elm_per_blk: usize, smd_per_blk: usize) -> proc_macro2::TokenStre
                 },
                 Dir::Bck => {
                   // Partial right shift
-                  gs.extend_one(quote! {
+                  gs.extend(quote! {
                     store(
                       pck_ptr.add(#pck_ptr_off),
                       rht(dlt, #shf_lit),
@@ -305,7 +305,7 @@ fn u32_blk_pck(elm_per_blk: usize, smd_per_blk: usize) -> proc_macro2::TokenStre
                 },
                 Dir::Fwd | Dir::FwdPrt => {
                   // Full left shift or Partial left shift
-                  gs.extend_one(quote! {
+                  gs.extend(quote! {
                     store(
                       pck_ptr.add(#pck_ptr_off),
                       or(smd_pck, lft(sub(load(unp_ptr.add(#unp_ptr_off)), prv), #shf_lit)),
@@ -319,7 +319,7 @@ fn u32_blk_pck(elm_per_blk: usize, smd_per_blk: usize) -> proc_macro2::TokenStre
 
         // --- arm: end
         // Push unrolled loop into arm definition
-        match_arm[3] = Group::new(g.delimiter(), gs.into()).into();
+        match_arm[3] = Group::new(g.delimiter(), gs).into();
       }
 
       // Push the match arm onto the match tree
@@ -328,25 +328,27 @@ fn u32_blk_pck(elm_per_blk: usize, smd_per_blk: usize) -> proc_macro2::TokenStre
 
     // Arm 32
     // No packing occurs at a bit-length of 32
-    // Copy raw bytes
-    gs_tree.extend_one(quote! {
+    // Copy the raw bytes of one block, `pck` may continue past the block
+    gs_tree.extend(quote! {
       32u8 => {
         // See https://doc.rust-lang.org/src/core/slice/mod.rs.html#3065
         ptr::copy_nonoverlapping(
           unp.as_ptr() as *const u8, 
           pck.as_mut_ptr(), 
-          pck.len());
+          #elm_per_blk * 4);
       },
       _ => panic!("unsupported bit-length {}", elm_bit_len)
     });
 
     // Push new elements into tree
-    match_tree.push(Group::new(g_tree.delimiter(), gs_tree.into()).into());
+    match_tree.push(Group::new(g_tree.delimiter(), gs_tree).into());
   }
   let match_arms: proc_macro2::TokenStream = match_tree.into_iter().collect();  
 
   // Create the pack method
   return quote! {
+    /// Requires a CPU with AVX2 on x86_64.
+    #[cfg_attr(target_arch = "x86_64", target_feature(enable = "avx2"))]
     pub unsafe fn #pck_name(elm_bit_len: u8, fst: u32, unp: &[u32], pck: &mut [u8]) {
       let unp_ptr = unp.as_ptr() as *const m256;
       let pck_ptr = pck.as_mut_ptr() as *mut m256;
@@ -372,12 +374,11 @@ fn u32_blk_unp(elm_per_blk: usize, smd_per_blk: usize) -> proc_macro2::TokenStre
 
     // Arm 0
     // No unpacking occurs at a bit-length of 0
-    // Generate zero values
-    gs_tree.extend_one(quote! {
+    // Every delta is zero, so every value is the first value
+    gs_tree.extend(quote! {
       0u8 => {
-        // `unp` expected to have an exact length of the block.
-        // `fill()` will overwrite the entire defined slice
-        unp.fill(0);
+        // `unp` may continue past the block, fill only the block
+        unp[..#elm_per_blk].fill(fst);
       },
     });
 
@@ -405,7 +406,7 @@ fn u32_blk_unp(elm_per_blk: usize, smd_per_blk: usize) -> proc_macro2::TokenStre
 
         // Create a bit-shift mask with the current arm's bit-length
         let msk_lit = proc_macro2::Literal::u32_suffixed(elm_bit_len as u32);
-        gs.extend_one(quote! {
+        gs.extend(quote! {
           let msk = set1(((1u32 << #msk_lit) - 1u32) as i32);
         });
 
@@ -415,7 +416,7 @@ fn u32_blk_unp(elm_per_blk: usize, smd_per_blk: usize) -> proc_macro2::TokenStre
             Itr::Fst => {
               // No shift for 1st SIMD lane
               // 1st SIMD vector starts with first u32 added to first SIMD vector
-              gs.extend_one(quote! {
+              gs.extend(quote! {
                 let mut smd_pck = load(pck_ptr);
                 let mut prv = set1(fst as i32);
                 let mut cur = add(prv, and(smd_pck, msk));
@@ -428,7 +429,7 @@ fn u32_blk_unp(elm_per_blk: usize, smd_per_blk: usize) -> proc_macro2::TokenStre
               match itm.shf_dir {
                 Dir::Zro => {
                   // No shift
-                  gs.extend_one(quote! {
+                  gs.extend(quote! {
                     cur = add(prv, and(smd_pck, msk));
                     store(unp_ptr.add(#unp_ptr_off), cur);
                     prv = cur;
@@ -438,7 +439,7 @@ fn u32_blk_unp(elm_per_blk: usize, smd_per_blk: usize) -> proc_macro2::TokenStre
                 Dir::Bck => {
                   // Partial left shift
                   let shf_lit = proc_macro2::Literal::i32_suffixed(itm.shf_len as i32);
-                  gs.extend_one(quote! {
+                  gs.extend(quote! {
                     cur = add(prv, or(dlt, and(lft(smd_pck, #shf_lit), msk)));
                     store(unp_ptr.add(#unp_ptr_off), cur);
                     prv = cur;
@@ -448,7 +449,7 @@ fn u32_blk_unp(elm_per_blk: usize, smd_per_blk: usize) -> proc_macro2::TokenStre
                 Dir::Fwd => {
                   // Full right shift
                   let shf_lit = proc_macro2::Literal::i32_suffixed(itm.shf_len as i32);
-                  gs.extend_one(quote! {
+                  gs.extend(quote! {
                     cur = add(prv, and(rht(smd_pck, #shf_lit), msk));
                     store(unp_ptr.add(#unp_ptr_off), cur);
                     prv = cur;
@@ -458,7 +459,7 @@ fn u32_blk_unp(elm_per_blk: usize, smd_per_blk: usize) -> proc_macro2::TokenStre
                 Dir::FwdPrt => {
                   // Partial right shift
                   let shf_lit = proc_macro2::Literal::i32_suffixed(itm.shf_len as i32);
-                  gs.extend_one(quote! {
+                  gs.extend(quote! {
                     let dlt = and(rht(smd_pck, #shf_lit), msk);
                   });
                 },
@@ -466,7 +467,7 @@ fn u32_blk_unp(elm_per_blk: usize, smd_per_blk: usize) -> proc_macro2::TokenStre
 
               // Load packed SIMD vector
               if itm.lne_bit_sum == BIT_PER_LNE {
-                gs.extend_one(quote! {
+                gs.extend(quote! {
                   smd_pck = load(pck_ptr.add(#pck_ptr_off));
                 });
                 pck_ptr_off += 1;
@@ -477,7 +478,7 @@ fn u32_blk_unp(elm_per_blk: usize, smd_per_blk: usize) -> proc_macro2::TokenStre
               match itm.shf_dir {
                 Dir::Zro => {
                   // No shift
-                  gs.extend_one(quote! {
+                  gs.extend(quote! {
                     store(
                       unp_ptr.add(#unp_ptr_off), 
                       add(prv, and(smd_pck, msk)),
@@ -486,7 +487,7 @@ fn u32_blk_unp(elm_per_blk: usize, smd_per_blk: usize) -> proc_macro2::TokenStre
                 },
                 Dir::Bck => {
                   // Partial left shift
-                  gs.extend_one(quote! {
+                  gs.extend(quote! {
                     store(
                       unp_ptr.add(#unp_ptr_off), 
                       add(prv, or(dlt, and(lft(smd_pck, #shf_lit), msk))),
@@ -495,7 +496,7 @@ fn u32_blk_unp(elm_per_blk: usize, smd_per_blk: usize) -> proc_macro2::TokenStre
                 },
                 Dir::Fwd | Dir::FwdPrt => {
                   // Full right shift or Partial right shift
-                  gs.extend_one(quote! {
+                  gs.extend(quote! {
                     store(
                       unp_ptr.add(#unp_ptr_off), 
                       add(prv, and(rht(smd_pck, #shf_lit), msk)),
@@ -509,7 +510,7 @@ fn u32_blk_unp(elm_per_blk: usize, smd_per_blk: usize) -> proc_macro2::TokenStre
 
         // --- arm: end
         // Push unrolled loop into arm definition
-        match_arm[3] = Group::new(g.delimiter(), gs.into()).into();
+        match_arm[3] = Group::new(g.delimiter(), gs).into();
       }
 
       // Push the match arm onto the match tree
@@ -518,24 +519,26 @@ fn u32_blk_unp(elm_per_blk: usize, smd_per_blk: usize) -> proc_macro2::TokenStre
 
     // Arm 32
     // No unpacking occurs at a bit-length of 32
-    // Copy raw bytes
-    gs_tree.extend_one(quote! {
+    // Copy the raw bytes of one block, `pck` may continue past the block
+    gs_tree.extend(quote! {
       32u8 => {
         ptr::copy_nonoverlapping(
           pck.as_ptr(), 
           unp.as_mut_ptr() as *mut u8, 
-          pck.len());
+          #elm_per_blk * 4);
       },
       _ => panic!("unsupported bit-length {}", elm_bit_len)
     });
 
     // Push new elements into tree
-    match_tree.push(Group::new(g_tree.delimiter(), gs_tree.into()).into());
+    match_tree.push(Group::new(g_tree.delimiter(), gs_tree).into());
   }
   let match_arms: proc_macro2::TokenStream = match_tree.into_iter().collect();  
 
   // Create the unpack method
   return quote! {
+    /// Requires a CPU with AVX2 on x86_64.
+    #[cfg_attr(target_arch = "x86_64", target_feature(enable = "avx2"))]
     pub unsafe fn #unp_name(elm_bit_len: u8, fst: u32, pck: &[u8], unp: &mut [u32]) {
       let pck_ptr = pck.as_ptr() as *const m256;
       let unp_ptr = unp.as_mut_ptr() as *mut m256;
@@ -546,57 +549,68 @@ fn u32_blk_unp(elm_per_blk: usize, smd_per_blk: usize) -> proc_macro2::TokenStre
 }
 
 // u32_blk_bit creates a u32 bit-length method as a TokenStream.
+//
+// The method uses stable AVX2 intrinsics, or portable SIMD with the `nightly` feature.
 fn u32_blk_bit(elm_per_blk: usize, smd_per_blk: usize) -> proc_macro2::TokenStream {
   // Create the method name
   let fn_name = proc_macro2::Ident::new(&format!("u32x{}_bit_len", elm_per_blk), Span::call_site());
 
-  // Create the pack method
-  let mut bit_method: Vec<TokenTree> = quote! {
+  // Unroll the loop calculation for each pair of neighboring SIMD vectors.
+  // Expect that the specified unpacked block is exactly `elm_per_blk` size.
+  let mut gs_smd = proc_macro2::TokenStream::new();
+  let mut gs_arc = proc_macro2::TokenStream::new();
+
+  // Use "n" as a multiplier for the starting index with "n * ELM_PER_SMD"
+  for n in 0..(smd_per_blk-1) {
+    let prv_idx: usize = n * ELM_PER_SMD;
+    let cur_idx: usize = (n + 1) * ELM_PER_SMD;
+    gs_smd.extend(quote! {
+      // Load the previous SIMD vector from an array
+      let prv_slc = &blk[#prv_idx..#prv_idx + #ELM_PER_SMD];
+      let prv = u32x8::from_array(*(prv_slc.as_ptr() as *const [u32; ELM_PER_SMD]));
+
+      // Load the current SIMD vector from an array
+      let cur_slc = &blk[#cur_idx..#cur_idx + #ELM_PER_SMD];
+      let cur = u32x8::from_array(*(cur_slc.as_ptr() as *const [u32; ELM_PER_SMD]));
+
+      // Delta encode and bitwise accumulate
+      acm |= (cur - prv).horizontal_or();
+
+      // There is no performance difference for loading "prv" and "cur" into variables
+      // based on Criterion micro benchmarks
+    });
+    gs_arc.extend(quote! {
+      // Delta encode the previous and current SIMD vectors, and bitwise accumulate lanes
+      let prv = load(blk[#prv_idx..#prv_idx + #ELM_PER_SMD].as_ptr() as *const m256);
+      let cur = load(blk[#cur_idx..#cur_idx + #ELM_PER_SMD].as_ptr() as *const m256);
+      acm_smd = or(acm_smd, sub(cur, prv));
+    });
+  }
+
+  return quote! {
+    #[cfg(feature = "nightly")]
     #[inline]
     pub unsafe fn #fn_name(blk: &[u32]) -> u8 {
       let mut acm = 0u32;
-    }
-  }
-  .into_iter()
-  .collect();
-
-  // Unroll the loop calculation with shift sizes at each iteration.
-  // Expect that the specified unpacked block is exactly `elm_per_blk` size.
-  if let TokenTree::Group(g) = bit_method.pop().unwrap() {
-    let mut gs = g.stream();
-
-    // Use "n" as a multiplier for the starting index with "n * ELM_PER_SMD"
-    for n in 0..(smd_per_blk-1) {
-      let prv_idx: usize = n * ELM_PER_SMD;
-      let cur_idx: usize = (n + 1) * ELM_PER_SMD;
-      gs.extend_one(quote! {
-        // Load the previous SIMD vector from an array
-        let prv_slc = &blk[#prv_idx..#prv_idx + #ELM_PER_SMD];
-        let prv = u32x8::from_array(*(prv_slc.as_ptr() as *const [u32; ELM_PER_SMD]));
-
-        // Load the current SIMD vector from an array
-        let cur_slc = &blk[#cur_idx..#cur_idx + #ELM_PER_SMD];
-        let cur = u32x8::from_array(*(cur_slc.as_ptr() as *const [u32; ELM_PER_SMD]));
-
-        // Delta encode and bitwise accumulate
-        acm |= (cur - prv).horizontal_or();
-
-        // There is no performance difference for loading "prv" and "cur" into variables
-        // based on Criterion micro benchmarks
-      });
+      #gs_smd
+      // Determine the number of least significant bits used
+      return (32u32 - u32::leading_zeros(acm)) as u8;
     }
 
-    gs.extend_one(quote! {
+    #[cfg(not(feature = "nightly"))]
+    #[inline]
+    #[cfg_attr(target_arch = "x86_64", target_feature(enable = "avx2"))]
+    pub unsafe fn #fn_name(blk: &[u32]) -> u8 {
+      let mut acm_smd = set1(0);
+      #gs_arc
+      // Bitwise accumulate across lanes
+      let mut lns = [0u32; ELM_PER_SMD];
+      store(lns.as_mut_ptr() as *mut m256, acm_smd);
+      let acm = lns.iter().fold(0u32, |acm, lne| acm | lne);
       // Determine the number of least significant bits used
       return (32u32 - u32::leading_zeros(acm)) as u8;
-    });
-
-    // Push unrolled loop into method definition
-    let gg = Group::new(g.delimiter(), gs.into());
-    bit_method.push(gg.into());
-  }
-
-  return bit_method.into_iter().collect();
+    }
+  };
 }
 
 // u32_blk_byt creates a byte-length method as a TokenStream.
@@ -619,7 +633,7 @@ fn u32_blk_byt(elm_per_blk: usize) -> proc_macro2::TokenStream {
     let mut gs = g.stream();
 
     // Set the zero bit length to have a byte length of zero
-    gs.extend_one(quote! {
+    gs.extend(quote! {
       0u8 => 0usize,
     });
 
@@ -648,20 +662,20 @@ fn u32_blk_byt(elm_per_blk: usize) -> proc_macro2::TokenStream {
       }
 
       // Write the pre-computed byte-length within the match arm
-      gs.extend_one(quote! {
+      gs.extend(quote! {
         #elm_bit_len => #byt_len,
       });
     }
 
     // Set the maximum byte length when the element bit-length is 32
     let byt_len: usize = BYT_PER_ELM * elm_per_blk;
-    gs.extend_one(quote! {
+    gs.extend(quote! {
       32u8 => #byt_len,
       _ => panic!("unsupported bit-length {}", elm_bit_len)
     });
 
     // Push unrolled loop into method definition
-    let gg = Group::new(g.delimiter(), gs.into());
+    let gg = Group::new(g.delimiter(), gs);
     match_tree.push(gg.into());
   }
   let match_arms: proc_macro2::TokenStream = match_tree.into_iter().collect();
@@ -697,28 +711,28 @@ pub fn u32_blk(input: TokenStream) -> TokenStream {
 
   // Validate min of elm_per_blk
   if elm_per_blk < MIN_ELM_PER_BLK {
-    elm_per_blk_lit
-      .span()
-      .unwrap()
-      .error(format!(
+    return syn::Error::new(
+      elm_per_blk_lit.span(),
+      format!(
         "parameter `elm_per_blk` is too small (min {})",
         MIN_ELM_PER_BLK
-      ))
-      .emit();
-    return TokenStream::new();
+      ),
+    )
+    .to_compile_error()
+    .into();
   }
 
   // Validate multiple of elm_per_blk
   if elm_per_blk % ELM_PER_SMD != 0 {
-    elm_per_blk_lit
-      .span()
-      .unwrap()
-      .error(format!(
+    return syn::Error::new(
+      elm_per_blk_lit.span(),
+      format!(
         "parameter `elm_per_blk` is not a multiple of {}",
         ELM_PER_SMD
-      ))
-      .emit();
-    return TokenStream::new();
+      ),
+    )
+    .to_compile_error()
+    .into();
   }
 
   // Calculate smd_per_blk
@@ -1,6 +1,9 @@
+use chrono::{Datelike, NaiveDate, Weekday};
 use criterion::{criterion_group, criterion_main, Criterion};
 use tms;
+use tms::bit;
 use tms::dat::goog;
+use tms::svb;
 use tms::vrn;
 use usize;
 
@@ -81,9 +84,243 @@ pub fn bench_smd(c: &mut Criterion) {
     });
   });
 
+  // Storing block base values costs a varint per block after the first
+  // goog::day() chained:44678 base:45030 cost:0.79%
+  let unp_exp = goog::day();
+  let day_chn = tms::DayLen::u32x256(&unp_exp);
+  let day_bse = tms::DayLen::u32x256_with(&unp_exp, true);
+  println!("day_u32x256 bytes chained:{} base:{} cost:{:.2}%", day_chn.byt_len, day_bse.byt_len,
+    100.0 * (day_bse.byt_len - day_chn.byt_len) as f64 / day_chn.byt_len as f64);
+  g.bench_function("day_u32x256_pck_bse", |b| {
+    let mut pck = vec![0u8; day_bse.byt_len];
+    b.iter(|| {
+      // [103.58 us 109.41 us 116.17 us]
+      tms::day_u32x256_pck(&day_bse, &unp_exp, &mut pck);
+    });
+  });
+  g.bench_function("day_u32x256_unp_bse", |b| {
+    let mut unp_act = vec![0u32; unp_exp.len()];
+    let mut pck = vec![0u8; day_bse.byt_len];
+    tms::day_u32x256_pck(&day_bse, &unp_exp, &mut pck);
+    b.iter(|| {
+      // [133.53 us 134.99 us 136.77 us]
+      tms::day_u32x256_unp(&pck, &mut unp_act);
+    });
+  });
+  g.bench_function("day_u32x256_blk_unp_lst", |b| {
+    let mut unp_act = vec![0u32; 256];
+    let mut pck = vec![0u8; day_bse.byt_len];
+    tms::day_u32x256_pck(&day_bse, &unp_exp, &mut pck);
+    let blks = tms::day_u32x256_blks(&pck).unwrap();
+    b.iter(|| {
+      // Random access to the last block
+      // [1.3312 us 1.3438 us 1.3575 us]
+      tms::day_u32x256_blk_unp(&pck, &blks[blks.len()-1], &mut unp_act);
+    });
+  });
+
+  // Block exceptions pack outlier deltas separately at a narrower block bit-length
+  // goog::day() chained:44678 exceptions:43648 saved:2.31%
+  let day_exc = tms::DayLen::u32x256_opt(&unp_exp, tms::PckOpt{exc: true, ..tms::PckOpt::default()});
+  println!("day_u32x256 bytes chained:{} exceptions:{} saved:{:.2}%", day_chn.byt_len, day_exc.byt_len,
+    100.0 * (day_chn.byt_len - day_exc.byt_len) as f64 / day_chn.byt_len as f64);
+  g.bench_function("day_u32x256_pck_exc", |b| {
+    let mut pck = vec![0u8; day_exc.byt_len];
+    b.iter(|| {
+      // [148.54 us 153.54 us 160.10 us]
+      tms::day_u32x256_pck(&day_exc, &unp_exp, &mut pck);
+    });
+  });
+  g.bench_function("day_u32x256_unp_exc", |b| {
+    let mut unp_act = vec![0u32; unp_exp.len()];
+    let mut pck = vec![0u8; day_exc.byt_len];
+    tms::day_u32x256_pck(&day_exc, &unp_exp, &mut pck);
+    b.iter(|| {
+      // [152.19 us 155.91 us 160.34 us]
+      tms::day_u32x256_unp(&pck, &mut unp_act);
+    });
+  });
+
+  // Appending epoch milliseconds skips the NaiveDateTime round trip
+  let date = NaiveDate::from_ymd(2020, 5, 4);
+  let ndts = tms::TmeMli::ndts(&unp_exp, date);
+  let mut tme = tms::TmeMli::new();
+  tme.append_day(&ndts).unwrap();
+  let mss = tme.get_day_epoch_ms(date).unwrap();
+  g.bench_function("append_day", |b| {
+    b.iter(|| {
+      // [488.28 us 496.84 us 505.60 us]
+      tms::TmeMli::new().append_day(&ndts).unwrap();
+    });
+  });
+  g.bench_function("append_day_epoch_ms", |b| {
+    b.iter(|| {
+      // [292.16 us 295.77 us 300.56 us]
+      tms::TmeMli::new().append_day_epoch_ms(&mss).unwrap();
+    });
+  });
+}
+
+pub fn bench_bit(c: &mut Criterion) {
+  let mut g = c.benchmark_group("bit");
+
+  // Block bytes of goog::day() by bit-length selection strategy
+  // OR and max select identical bit-lengths, exceptions select narrower bit-lengths
+  // goog::day() block bytes or:44320 exc:43290 exceptions:463 saved:2.32%
+  let unp = goog::day();
+  let mut dlts = vec![0u32; 256];
+  let (mut byt_or, mut byt_exc, mut exc_cnt) = (0usize, 0usize, 0usize);
+  for n in 0..unp.len() / 256 {
+    let fst = if n == 0 { unp[0] } else { unp[n*256-1] };
+    bit::blk_dlts(&unp[n*256..(n+1)*256], fst, &mut dlts);
+    byt_or += bit::bit_len_or(&dlts).byt_len;
+    let sel = bit::bit_len_exc(&dlts);
+    byt_exc += sel.byt_len;
+    exc_cnt += sel.exc_cnt;
+  }
+  println!("goog::day() block bytes or:{} exc:{} exceptions:{} saved:{:.2}%", byt_or, byt_exc, exc_cnt,
+    100.0 * (byt_or - byt_exc) as f64 / byt_or as f64);
+
+  g.bench_function("u32x256_bit_len_or", |b| {
+    let blk = goog::blk256();
+    b.iter(|| {
+      unsafe {
+        // [21.161 ns 22.000 ns 22.900 ns]
+        tms::u32x256_bit_len(&blk)
+      }
+    });
+  });
+  g.bench_function("u32x256_bit_len_max", |b| {
+    let blk = goog::blk256();
+    b.iter(|| {
+      unsafe {
+        // [15.507 ns 15.785 ns 16.071 ns]
+        bit::u32x256_bit_len_max(&blk)
+      }
+    });
+  });
+  g.bench_function("bit_len_exc", |b| {
+    let blk = goog::blk256();
+    let mut dlts = vec![0u32; 256];
+    b.iter(|| {
+      // [681.84 ns 691.61 ns 703.12 ns]
+      bit::blk_dlts(&blk, blk[0], &mut dlts);
+      bit::bit_len_exc(&dlts)
+    });
+  });
+}
+
+pub fn bench_svb(c: &mut Criterion) {
+  let mut g = c.benchmark_group("svb");
+
+  // Bytes of goog::blk255(), the tail of a day or a thin day, by encoding
+  // goog::blk255() bytes vrn:713 svb:682 svb_dlt:386
+  let unp_exp = goog::blk255();
+  println!("goog::blk255() bytes vrn:{} svb:{} svb_dlt:{}", vrn::u32s_byt_len(&unp_exp),
+    svb::u32s_byt_len(&unp_exp), svb::u32s_dlt_byt_len(&unp_exp, 0));
+
+  g.bench_function("vrn_u32s_pck", |b| {
+    let mut pck = vec![0u8; vrn::u32s_byt_len(&unp_exp)];
+    b.iter(|| {
+      // [816.21 ns 824.56 ns 834.06 ns]
+      vrn::u32s_pck(&unp_exp, &mut pck);
+    });
+  });
+  g.bench_function("vrn_u32s_unp", |b| {
+    let mut pck = vec![0u8; vrn::u32s_byt_len(&unp_exp)];
+    vrn::u32s_pck(&unp_exp, &mut pck);
+    let mut unp_act = vec![0u32; unp_exp.len()];
+    b.iter(|| {
+      // Varint decoding expects a zeroed destination
+      // [1.3510 us 1.4230 us 1.5065 us]
+      unp_act.fill(0);
+      vrn::u32s_unp(&pck, &mut unp_act);
+    });
+  });
+  g.bench_function("svb_u32s_pck", |b| {
+    let mut pck = vec![0u8; svb::u32s_byt_len(&unp_exp)];
+    b.iter(|| {
+      // [922.76 ns 955.43 ns 989.95 ns]
+      svb::u32s_pck(&unp_exp, &mut pck)
+    });
+  });
+  g.bench_function("svb_u32s_unp", |b| {
+    let mut pck = vec![0u8; svb::u32s_byt_len(&unp_exp)];
+    svb::u32s_pck(&unp_exp, &mut pck);
+    let mut unp_act = vec![0u32; unp_exp.len()];
+    b.iter(|| {
+      // [107.14 ns 112.27 ns 117.20 ns]
+      unsafe { svb::u32s_unp(&pck, &mut unp_act) }
+    });
+  });
+  g.bench_function("svb_u32s_dlt_pck", |b| {
+    let mut pck = vec![0u8; svb::u32s_dlt_byt_len(&unp_exp, 0)];
+    b.iter(|| {
+      // [650.99 ns 677.40 ns 709.25 ns]
+      svb::u32s_dlt_pck(&unp_exp, 0, &mut pck)
+    });
+  });
+  g.bench_function("svb_u32s_dlt_unp", |b| {
+    let mut pck = vec![0u8; svb::u32s_dlt_byt_len(&unp_exp, 0)];
+    svb::u32s_dlt_pck(&unp_exp, 0, &mut pck);
+    let mut unp_act = vec![0u32; unp_exp.len()];
+    b.iter(|| {
+      // [134.85 ns 139.39 ns 144.27 ns]
+      unsafe { svb::u32s_dlt_unp(&pck, 0, &mut unp_act) }
+    });
+  });
 }
 
+pub fn bench_year(c: &mut Criterion) {
+  let mut g = c.benchmark_group("year");
+  g.sample_size(10);
+
+  // Every weekday of 2020 holds goog::day()
+  let unp = goog::day();
+  let mut tme = tms::TmeMli::new();
+  let mut dates = Vec::new();
+  let mut date = NaiveDate::from_ymd(2020, 1, 1);
+  while date.year() == 2020 {
+    if date.weekday() != Weekday::Sat && date.weekday() != Weekday::Sun {
+      tme.append_day(&tms::TmeMli::ndts(&unp, date)).unwrap();
+      dates.push(date);
+    }
+    date = date.succ();
+  }
+
+  // Bytes of the year, against the same ticks written to parquet with pqt::props()
+  // year bytes tms:11708680 parquet:10502680
+  #[cfg(feature = "parquet")]
+  {
+    let mut pqt = Vec::new();
+    tms::pqt::write(&tme, dates[0], dates[dates.len() - 1], &mut pqt).unwrap();
+    println!("year bytes tms:{} parquet:{}", tme.buf.len(), pqt.len());
+  }
 
+  g.bench_function("get_day", |b| {
+    b.iter(|| {
+      for date in &dates {
+        tme.get_day(*date);
+      }
+    });
+  });
+  g.bench_function("get_day_into", |b| {
+    let mut out = Vec::new();
+    b.iter(|| {
+      for date in &dates {
+        tme.get_day_into(*date, &mut out);
+      }
+    });
+  });
+  g.bench_function("get_day_i64_into", |b| {
+    let mut out = Vec::new();
+    b.iter(|| {
+      for date in &dates {
+        tme.get_day_i64_into(*date, &mut out);
+      }
+    });
+  });
+}
 
-criterion_group!(benches, bench_vrn, bench_smd);
+criterion_group!(benches, bench_vrn, bench_smd, bench_bit, bench_svb, bench_year);
 criterion_main!(benches);
@@ -0,0 +1,187 @@
+//! `arw` module converts `TmeMli` days and ticks to and from Apache Arrow `RecordBatch`es.
+//!
+//! Timestamps are a `Timestamp(Millisecond, "America/New_York")` column named `time`.
+//! Days are decoded straight into the i64 timestamp buffer without allocating `NaiveDateTime`s.
+
+use std::sync::Arc;
+use anyhow::{anyhow, bail, Result};
+use arrow::array::{Array, ArrayRef, AsArray, Float64Array, TimestampMillisecondArray};
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use arrow::datatypes::{TimestampMicrosecondType, TimestampMillisecondType, TimestampNanosecondType, TimestampSecondType};
+use arrow::record_batch::RecordBatch;
+use chrono::{NaiveDate, NaiveDateTime, TimeZone, Utc};
+use crate::{DayRef, TmeMli, TZ_EXC};
+use crate::imp::{self, duk::Tck};
+
+/// `TME_COL` is the name of the timestamp column.
+pub const TME_COL: &str = "time";
+
+/// `tme_typ` returns the Arrow type of the timestamp column.
+pub fn tme_typ() -> DataType {
+  return DataType::Timestamp(TimeUnit::Millisecond, Some(TZ_EXC.name().into()));
+}
+
+/// `schema` returns the schema of a day `RecordBatch`.
+pub fn schema() -> SchemaRef {
+  return Arc::new(Schema::new(vec![Field::new(TME_COL, tme_typ(), false)]));
+}
+
+/// `day_arr` converts a day to a timestamp array.
+pub fn day_arr(day: &DayRef) -> TimestampMillisecondArray {
+  let mut out = Vec::with_capacity(day.len());
+  day.i64s_ext(&mut out);
+  return TimestampMillisecondArray::from(out).with_timezone(TZ_EXC.name());
+}
+
+/// `day_batch` converts a day to a `RecordBatch` with a single timestamp column.
+pub fn day_batch(day: &DayRef) -> RecordBatch {
+  return RecordBatch::try_new(schema(), vec![Arc::new(day_arr(day))]).unwrap();
+}
+
+/// `batch` converts days from `fst` to `lst` inclusive to a single `RecordBatch`.
+pub fn batch(tme: &TmeMli, fst: NaiveDate, lst: NaiveDate) -> RecordBatch {
+  let mut out = Vec::new();
+  for (_, day) in tme.days().filter(|(date, _)| *date >= fst && *date <= lst) {
+    day.i64s_ext(&mut out);
+  }
+  let arr = TimestampMillisecondArray::from(out).with_timezone(TZ_EXC.name());
+  return RecordBatch::try_new(schema(), vec![Arc::new(arr)]).unwrap();
+}
+
+/// `tcks_batch` converts ticks to a `RecordBatch` with timestamp, ask, bid, ask volume, and bid volume columns.
+///
+/// Ambiguous exchange times take the earlier offset. Times skipped by a daylight saving time transition are an error naming the row.
+pub fn tcks_batch(tcks: &[Tck]) -> Result<RecordBatch> {
+  let mut tmes = Vec::with_capacity(tcks.len());
+  for (row, tck) in tcks.iter().enumerate() {
+    match TZ_EXC.from_local_datetime(&tck.tme).earliest() {
+      Some(tme) => tmes.push(tme.timestamp_millis()),
+      None => bail!("row {}: time {} doesn't exist in {}", row, tck.tme, TZ_EXC),
+    }
+  }
+  let col = |f: fn(&Tck) -> f64| -> ArrayRef { Arc::new(Float64Array::from_iter_values(tcks.iter().map(f))) };
+  let schema = Schema::new(vec![
+    Field::new(TME_COL, tme_typ(), false),
+    Field::new("ask", DataType::Float64, false),
+    Field::new("bid", DataType::Float64, false),
+    Field::new("ask_vol", DataType::Float64, false),
+    Field::new("bid_vol", DataType::Float64, false),
+  ]);
+  return Ok(RecordBatch::try_new(Arc::new(schema), vec![
+    Arc::new(TimestampMillisecondArray::from(tmes).with_timezone(TZ_EXC.name())),
+    col(|tck| tck.ask),
+    col(|tck| tck.bid),
+    col(|tck| tck.ask_vol),
+    col(|tck| tck.bid_vol),
+  ])?);
+}
+
+/// `tmes` returns the exchange times of a timestamp column.
+///
+/// The column may have any timestamp unit and timezone. Sub-millisecond precision is truncated.
+pub fn tmes(bat: &RecordBatch, col: &str) -> Result<Vec<NaiveDateTime>> {
+  let arr = bat.column_by_name(col).ok_or_else(|| anyhow!("column {:?} not found", col))?;
+  if arr.null_count() != 0 {
+    bail!("column {:?} has {} nulls", col, arr.null_count());
+  }
+  let mss: Vec<i64> = match arr.data_type() {
+    DataType::Timestamp(TimeUnit::Second, _) => arr.as_primitive::<TimestampSecondType>().values().iter().map(|v| v.saturating_mul(1_000)).collect(),
+    DataType::Timestamp(TimeUnit::Millisecond, _) => arr.as_primitive::<TimestampMillisecondType>().values().to_vec(),
+    DataType::Timestamp(TimeUnit::Microsecond, _) => arr.as_primitive::<TimestampMicrosecondType>().values().iter().map(|v| v.div_euclid(1_000)).collect(),
+    DataType::Timestamp(TimeUnit::Nanosecond, _) => arr.as_primitive::<TimestampNanosecondType>().values().iter().map(|v| v.div_euclid(1_000_000)).collect(),
+    typ => bail!("column {:?} has type {} instead of a timestamp", col, typ),
+  };
+  let mut tmes = Vec::with_capacity(mss.len());
+  for (row, ms) in mss.iter().enumerate() {
+    match Utc.timestamp_millis_opt(*ms).single() {
+      Some(tme) => tmes.push(tme.with_timezone(&TZ_EXC).naive_local()),
+      None => bail!("column {:?} row {}: timestamp is out of range", col, row),
+    }
+  }
+  return Ok(tmes);
+}
+
+/// `insert_batch` inserts each trading day of a timestamp column into a `TmeMli`.
+///
+/// Times outside the trading session are dropped.
+pub fn insert_batch(tme: &mut TmeMli, bat: &RecordBatch, col: &str) -> Result<()> {
+  return imp::insert_days(tme, &tmes(bat, col)?);
+}
+
+/// `from_batches` reads the timestamp column of `RecordBatch`es into a new `TmeMli`.
+///
+/// A trading day may span consecutive batches.
+pub fn from_batches(bats: &[RecordBatch], col: &str) -> Result<TmeMli> {
+  let mut all = Vec::new();
+  for bat in bats {
+    all.extend(tmes(bat, col)?);
+  }
+  let mut tme = TmeMli::new();
+  imp::insert_days(&mut tme, &all)?;
+  return Ok(tme);
+}
+
+#[cfg(test)]
+mod tst {
+  use super::*;
+  use arrow::array::TimestampNanosecondArray;
+
+  #[test]
+  fn day_batch_() {
+    let date = NaiveDate::from_ymd(2020, 5, 4);
+    let mut tme = TmeMli::new();
+    tme.append_day(&[date.and_hms_milli(9, 30, 0, 143), date.and_hms_milli(15, 59, 59, 999)]).unwrap();
+    let bat = day_batch(&tme.day(date).unwrap());
+    assert_eq!(schema(), bat.schema());
+    let arr = bat.column(0).as_primitive::<TimestampMillisecondType>();
+    assert_eq!(&[1588599000143, 1588622399999], arr.values().as_ref());
+    assert_eq!(tme.get_day(date).unwrap(), tmes(&bat, TME_COL).unwrap());
+  }
+
+  #[test]
+  fn batch_round_trip() {
+    let mut tme = TmeMli::new();
+    for date in [NaiveDate::from_ymd(2020, 5, 4), NaiveDate::from_ymd(2020, 5, 6)] {
+      let day: Vec<NaiveDateTime> = crate::dat::goog::day().iter()
+        .map(|v| date.and_hms(9, 30, 0) + chrono::Duration::milliseconds(*v as i64))
+        .collect();
+      tme.append_day(&day).unwrap();
+    }
+    let bat = batch(&tme, tme.first_day().unwrap(), tme.last_day().unwrap());
+    assert_eq!(tme.len_ticks(), bat.num_rows());
+    assert_eq!(tme.buf, from_batches(&[bat], TME_COL).unwrap().buf);
+  }
+
+  #[test]
+  fn insert_batch_nano_utc() {
+    let arr = TimestampNanosecondArray::from(vec![1588599000143000000, 1588599000193999999]).with_timezone("UTC");
+    let schema = Schema::new(vec![Field::new("ts", arr.data_type().clone(), false)]);
+    let bat = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arr)]).unwrap();
+    let mut tme = TmeMli::new();
+    insert_batch(&mut tme, &bat, "ts").unwrap();
+    let date = NaiveDate::from_ymd(2020, 5, 4);
+    assert_eq!(vec![date.and_hms_milli(9, 30, 0, 143), date.and_hms_milli(9, 30, 0, 193)], tme.get_day(date).unwrap());
+    assert!(insert_batch(&mut tme, &bat, "time").is_err());
+
+    // Out of range timestamps are an error naming the row
+    let arr = TimestampMillisecondArray::from(vec![1588599000143, i64::MAX]);
+    let schema = Schema::new(vec![Field::new("ts", arr.data_type().clone(), false)]);
+    let bat = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(arr)]).unwrap();
+    let err = tmes(&bat, "ts").unwrap_err().to_string();
+    assert!(err.contains("row 1"), "{}", err);
+  }
+
+  #[test]
+  fn tcks_batch_() {
+    let tck = Tck{tme: NaiveDate::from_ymd(2020, 5, 4).and_hms_milli(9, 30, 0, 143), ask: 2.0, bid: 1.0, ask_vol: 0.5, bid_vol: 0.25};
+    let bat = tcks_batch(&[tck]).unwrap();
+    assert_eq!(5, bat.num_columns());
+    assert_eq!(&[1588599000143], bat.column(0).as_primitive::<TimestampMillisecondType>().values().as_ref());
+    assert_eq!(1.0, bat.column_by_name("bid").unwrap().as_primitive::<arrow::datatypes::Float64Type>().value(0));
+
+    // 2:30am on 2020-03-08 is skipped by the daylight saving time transition
+    let gap = Tck{tme: NaiveDate::from_ymd(2020, 3, 8).and_hms(2, 30, 0), ..tck};
+    let err = tcks_batch(&[tck, gap]).unwrap_err().to_string();
+    assert!(err.contains("row 1"), "{}", err);
+  }
+}
@@ -0,0 +1,251 @@
+//! `exp` module exports `TmeMli` days to CSV and JSON Lines.
+//!
+//! `Exp` is configured with builder methods for the timestamp format, timezone, delimiter,
+//! and value columns accompanying the timestamps.
+//!
+//! ```ignore
+//! let exp = Exp::new().tz(chrono_tz::UTC).col("px", &pxs);
+//! exp.write_csv(&tme, fst, lst, File::create("ticks.csv")?)?;
+//! ```
+
+use std::io::Write;
+use anyhow::{bail, Result};
+use chrono::{DateTime, NaiveDate, SecondsFormat};
+use chrono_tz::Tz;
+use crate::{TmeMli, TZ_EXC};
+use crate::imp::csv::{EpochUnit, TmeFmt};
+
+/// `Exp` exports timestamps and value columns.
+#[derive(Debug, Clone)]
+pub struct Exp<'a> {
+  fmt: TmeFmt,
+  tz: Tz,
+  dlm: u8,
+  hdr: String,
+  cols: Vec<(String, &'a [f64])>,
+}
+
+impl<'a> Default for Exp<'a> {
+  /// `default` returns an `Exp` writing RFC 3339 timestamps in the exchange timezone to a `time` column.
+  fn default() -> Exp<'a> {
+    return Exp{
+      fmt: TmeFmt::Iso8601,
+      tz: TZ_EXC,
+      dlm: b',',
+      hdr: "time".to_string(),
+      cols: Vec::new(),
+    };
+  }
+}
+
+impl<'a> Exp<'a> {
+  /// `new` returns the `default` `Exp`.
+  pub fn new() -> Exp<'a> {
+    return Exp::default();
+  }
+
+  /// `iso8601` writes RFC 3339 timestamps with millisecond precision and an offset.
+  pub fn iso8601(mut self) -> Exp<'a> {
+    self.fmt = TmeFmt::Iso8601;
+    return self;
+  }
+
+  /// `fmt` writes timestamps with a chrono format string, such as `%Y-%m-%d %H:%M:%S%.3f`.
+  pub fn fmt(mut self, fmt: &str) -> Exp<'a> {
+    self.fmt = TmeFmt::Fmt(fmt.to_string());
+    return self;
+  }
+
+  /// `epoch` writes integer Unix epoch timestamps in the specified unit.
+  pub fn epoch(mut self, unt: EpochUnit) -> Exp<'a> {
+    self.fmt = TmeFmt::Epoch(unt);
+    return self;
+  }
+
+  /// `tz` sets the timezone of written timestamps.
+  pub fn tz(mut self, tz: Tz) -> Exp<'a> {
+    self.tz = tz;
+    return self;
+  }
+
+  /// `delimiter` sets the CSV field delimiter.
+  pub fn delimiter(mut self, dlm: u8) -> Exp<'a> {
+    self.dlm = dlm;
+    return self;
+  }
+
+  /// `time_name` sets the name of the timestamp column.
+  pub fn time_name(mut self, name: &str) -> Exp<'a> {
+    self.hdr = name.to_string();
+    return self;
+  }
+
+  /// `col` adds a value column.
+  ///
+  /// `vals` has one value per exported timestamp in date order.
+  pub fn col(mut self, name: &str, vals: &'a [f64]) -> Exp<'a> {
+    self.cols.push((name.to_string(), vals));
+    return self;
+  }
+
+  /// `write_csv` writes days from `fst` to `lst` inclusive as CSV with a header row.
+  pub fn write_csv<W: Write>(&self, tme: &TmeMli, fst: NaiveDate, lst: NaiveDate, wtr: W) -> Result<()> {
+    let tmes = self.tmes(tme, fst, lst)?;
+    let mut wtr = ::csv::WriterBuilder::new().delimiter(self.dlm).from_writer(wtr);
+    let mut rec: Vec<String> = Vec::with_capacity(self.cols.len() + 1);
+    rec.push(self.hdr.clone());
+    rec.extend(self.cols.iter().map(|(name, _)| name.clone()));
+    wtr.write_record(&rec)?;
+    for (n, tme) in tmes.iter().enumerate() {
+      rec.clear();
+      rec.push(self.fmt_tme(tme));
+      rec.extend(self.cols.iter().map(|(_, vals)| vals[n].to_string()));
+      wtr.write_record(&rec)?;
+    }
+    wtr.flush()?;
+    return Ok(());
+  }
+
+  /// `write_jsonl` writes days from `fst` to `lst` inclusive as JSON Lines, one object per timestamp.
+  ///
+  /// Epoch timestamps are JSON numbers, other timestamps are JSON strings. Non-finite values are `null`.
+  pub fn write_jsonl<W: Write>(&self, tme: &TmeMli, fst: NaiveDate, lst: NaiveDate, mut wtr: W) -> Result<()> {
+    let tmes = self.tmes(tme, fst, lst)?;
+    let hdr = jsn_str(&self.hdr);
+    let names: Vec<String> = self.cols.iter().map(|(name, _)| jsn_str(name)).collect();
+    let mut lne = String::new();
+    for (n, tme) in tmes.iter().enumerate() {
+      lne.clear();
+      lne.push('{');
+      lne.push_str(&hdr);
+      lne.push(':');
+      match self.fmt {
+        TmeFmt::Epoch(_) => lne.push_str(&self.fmt_tme(tme)),
+        _ => lne.push_str(&jsn_str(&self.fmt_tme(tme))),
+      }
+      for (name, (_, vals)) in names.iter().zip(self.cols.iter()) {
+        lne.push(',');
+        lne.push_str(name);
+        lne.push(':');
+        if vals[n].is_finite() {
+          lne.push_str(&vals[n].to_string());
+        } else {
+          lne.push_str("null");
+        }
+      }
+      lne.push_str("}\n");
+      wtr.write_all(lne.as_bytes())?;
+    }
+    wtr.flush()?;
+    return Ok(());
+  }
+
+  /// `tmes` returns the timestamps of days from `fst` to `lst` inclusive and checks value column lengths.
+  fn tmes(&self, tme: &TmeMli, fst: NaiveDate, lst: NaiveDate) -> Result<Vec<DateTime<Tz>>> {
+    let mut tmes = Vec::new();
+    for (date, _) in tme.days().filter(|(date, _)| *date >= fst && *date <= lst) {
+      tmes.extend(tme.get_day_tz(date, &self.tz).unwrap());
+    }
+    for (name, vals) in &self.cols {
+      if vals.len() != tmes.len() {
+        bail!("column {:?} has {} values for {} timestamps", name, vals.len(), tmes.len());
+      }
+    }
+    return Ok(tmes);
+  }
+
+  /// `fmt_tme` formats a timestamp.
+  fn fmt_tme(&self, tme: &DateTime<Tz>) -> String {
+    return match &self.fmt {
+      TmeFmt::Iso8601 => tme.to_rfc3339_opts(SecondsFormat::Millis, true),
+      TmeFmt::Fmt(fmt) => tme.format(fmt).to_string(),
+      TmeFmt::Epoch(unt) => {
+        let mli = tme.timestamp_millis();
+        match unt {
+          EpochUnit::Sec => tme.timestamp().to_string(),
+          EpochUnit::Mli => mli.to_string(),
+          EpochUnit::Mcr => (mli * 1_000).to_string(),
+          EpochUnit::Nno => (mli * 1_000_000).to_string(),
+        }
+      },
+    };
+  }
+}
+
+/// `jsn_str` returns a quoted and escaped JSON string.
+fn jsn_str(s: &str) -> String {
+  let mut ret = String::with_capacity(s.len() + 2);
+  ret.push('"');
+  for c in s.chars() {
+    match c {
+      '"' => ret.push_str("\\\""),
+      '\\' => ret.push_str("\\\\"),
+      '\n' => ret.push_str("\\n"),
+      '\r' => ret.push_str("\\r"),
+      '\t' => ret.push_str("\\t"),
+      c if (c as u32) < 0x20 => ret.push_str(&format!("\\u{:04x}", c as u32)),
+      c => ret.push(c),
+    }
+  }
+  ret.push('"');
+  return ret;
+}
+
+#[cfg(test)]
+mod tst {
+  use super::*;
+  use crate::imp::csv::CsvImp;
+
+  fn tme_two_days() -> TmeMli {
+    let mut tme = TmeMli::new();
+    let date1 = NaiveDate::from_ymd(2020, 5, 4);
+    let date2 = NaiveDate::from_ymd(2020, 5, 5);
+    tme.append_day(&[date1.and_hms_milli(9, 30, 0, 143), date1.and_hms_milli(9, 30, 0, 193)]).unwrap();
+    tme.append_day(&[date2.and_hms(9, 30, 0)]).unwrap();
+    return tme;
+  }
+
+  #[test]
+  fn write_csv_() {
+    let tme = tme_two_days();
+    let pxs = [1309.5, 1309.75, 1310.0];
+    let mut out = Vec::new();
+    let fst = NaiveDate::from_ymd(2020, 5, 1);
+    let lst = NaiveDate::from_ymd(2020, 5, 31);
+    Exp::new().col("px", &pxs).write_csv(&tme, fst, lst, &mut out).unwrap();
+    assert_eq!("time,px\n2020-05-04T09:30:00.143-04:00,1309.5\n2020-05-04T09:30:00.193-04:00,1309.75\n2020-05-05T09:30:00.000-04:00,1310\n", String::from_utf8(out).unwrap());
+
+    // round trip through the importer
+    let mut out = Vec::new();
+    Exp::new().epoch(EpochUnit::Mli).write_csv(&tme, fst, lst, &mut out).unwrap();
+    let mut act = TmeMli::new();
+    CsvImp::new().epoch(EpochUnit::Mli).insert(&mut act, &out[..]).unwrap();
+    assert_eq!(tme.buf, act.buf);
+  }
+
+  #[test]
+  fn write_csv_range_tz() {
+    let tme = tme_two_days();
+    let date = NaiveDate::from_ymd(2020, 5, 5);
+    let mut out = Vec::new();
+    Exp::new().fmt("%Y-%m-%d %H:%M:%S%.3f").tz(chrono_tz::UTC).delimiter(b';').time_name("ts")
+      .write_csv(&tme, date, date, &mut out).unwrap();
+    assert_eq!("ts\n2020-05-05 13:30:00.000\n", String::from_utf8(out).unwrap());
+    assert!(Exp::new().col("px", &[1.0]).write_csv(&tme, date, NaiveDate::from_ymd(2020, 5, 6), Vec::new()).is_ok());
+    assert!(Exp::new().col("px", &[1.0]).write_csv(&tme, NaiveDate::from_ymd(2020, 5, 4), date, Vec::new()).is_err());
+  }
+
+  #[test]
+  fn write_jsonl_() {
+    let tme = tme_two_days();
+    let pxs = [1309.5, f64::NAN, 1310.0];
+    let mut out = Vec::new();
+    let fst = NaiveDate::from_ymd(2020, 5, 4);
+    let lst = NaiveDate::from_ymd(2020, 5, 5);
+    Exp::new().epoch(EpochUnit::Mli).col("p\"x", &pxs).write_jsonl(&tme, fst, lst, &mut out).unwrap();
+    assert_eq!("{\"time\":1588599000143,\"p\\\"x\":1309.5}\n{\"time\":1588599000193,\"p\\\"x\":null}\n{\"time\":1588685400000,\"p\\\"x\":1310}\n", String::from_utf8(out).unwrap());
+    let mut out = Vec::new();
+    Exp::new().tz(chrono_tz::UTC).write_jsonl(&tme, lst, lst, &mut out).unwrap();
+    assert_eq!("{\"time\":\"2020-05-05T13:30:00.000Z\"}\n", String::from_utf8(out).unwrap());
+  }
+}
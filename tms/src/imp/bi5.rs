@@ -0,0 +1,213 @@
+//! `bi5` module imports Dukascopy native `.bi5` tick files.
+//!
+//! A `.bi5` file is an hour of ticks compressed with LZMA.
+//!
+//! Files are stored in a directory tree by symbol and UTC hour:
+//!
+//! ```text
+//! GOOG/2020/04/04/13h_ticks.bi5
+//! ```
+//!
+//! The month directory is zero-based, so `04` is May.
+//!
+//! Decompressed ticks are 20-byte big-endian records:
+//!
+// +-----------------+-------+-------+------------+------------+
+// |    Ms Offset    |  Ask  |  Bid  | Ask Volume | Bid Volume |
+// +-----------------+-------+-------+------------+------------+
+// | u32 from hour   | u32   | u32   | f32        | f32        |
+// +-----------------+-------+-------+------------+------------+
+//
+//   Ms Offset  - Milliseconds from the start of the UTC hour.
+//   Ask, Bid   - Prices as integer multiples of the symbol's point size.
+
+use std::fs::{self, File};
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
+use anyhow::{anyhow, bail, Result};
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+use crate::TmeMli;
+use crate::imp::duk::{self, Tck};
+
+/// `LEN_REC` is the number of bytes in a decompressed tick record.
+pub const LEN_REC: usize = 20;
+
+/// `dcd` decodes decompressed tick records for the specified UTC hour.
+///
+/// `pnt` is the price point size, such as `0.001` for US stocks and `0.00001` for most currency pairs.
+pub fn dcd(raw: &[u8], hr: DateTime<Utc>, pnt: f64) -> Result<Vec<Tck>> {
+  if raw.len() % LEN_REC != 0 {
+    bail!("{} bytes is not a multiple of the {} byte record length", raw.len(), LEN_REC);
+  }
+  let u32_at = |rec: &[u8], idx: usize| u32::from_be_bytes([rec[idx], rec[idx+1], rec[idx+2], rec[idx+3]]);
+  // Convert UTC times to the exchange timezone
+  let tmes: Vec<DateTime<Utc>> = raw.chunks_exact(LEN_REC)
+    .map(|rec| hr + Duration::milliseconds(u32_at(rec, 0) as i64))
+    .collect();
+  let tmes = TmeMli::ndts_exc(&tmes);
+  let mut tcks = Vec::with_capacity(tmes.len());
+  for (rec, tme) in raw.chunks_exact(LEN_REC).zip(tmes) {
+    tcks.push(Tck{
+      tme,
+      ask: u32_at(rec, 4) as f64 * pnt,
+      bid: u32_at(rec, 8) as f64 * pnt,
+      ask_vol: f32::from_bits(u32_at(rec, 12)) as f64,
+      bid_vol: f32::from_bits(u32_at(rec, 16)) as f64,
+    });
+  }
+  return Ok(tcks);
+}
+
+/// `read` decompresses and decodes an LZMA compressed hour of ticks.
+///
+/// An empty input is an hour without ticks.
+pub fn read<R: Read>(rdr: R, hr: DateTime<Utc>, pnt: f64) -> Result<Vec<Tck>> {
+  let mut rdr = BufReader::new(rdr);
+  let mut raw = Vec::new();
+  let mut cmp = Vec::new();
+  rdr.read_to_end(&mut cmp)?;
+  if cmp.len() == 0 {
+    return Ok(Vec::new());
+  }
+  lzma_rs::lzma_decompress(&mut cmp.as_slice(), &mut raw)
+    .map_err(|e| anyhow!("lzma: {:?}", e))?;
+  return dcd(&raw, hr, pnt);
+}
+
+/// `hr_of_path` returns the UTC hour of a `.bi5` file from its `YYYY/MM/DD/HHh_ticks.bi5` path.
+pub fn hr_of_path(pth: &Path) -> Result<DateTime<Utc>> {
+  let prs = || -> Option<DateTime<Utc>> {
+    let mut cmps = pth.iter().rev().map(|c| c.to_str());
+    let hr: u32 = cmps.next()??.strip_suffix("h_ticks.bi5")?.parse().ok()?;
+    let day: u32 = cmps.next()??.parse().ok()?;
+    let mon: u32 = cmps.next()??.parse().ok()?;
+    let yr: i32 = cmps.next()??.parse().ok()?;
+    // Months are zero-based
+    let date = NaiveDate::from_ymd_opt(yr, mon + 1, day)?;
+    return Some(Utc.from_utc_datetime(&date.and_hms_opt(hr, 0, 0)?));
+  };
+  return prs().ok_or_else(|| anyhow!("{}: expected a YYYY/MM/DD/HHh_ticks.bi5 path", pth.display()));
+}
+
+/// `read_path` reads a `.bi5` file with the hour taken from its path.
+pub fn read_path<P: AsRef<Path>>(pth: P, pnt: f64) -> Result<Vec<Tck>> {
+  let pth = pth.as_ref();
+  let hr = hr_of_path(pth)?;
+  let fle = File::open(pth).map_err(|e| anyhow!("{}: {}", pth.display(), e))?;
+  return read(fle, hr, pnt).map_err(|e| anyhow!("{}: {}", pth.display(), e));
+}
+
+/// `pths` returns the `.bi5` file paths within a directory tree in hour order.
+pub fn pths<P: AsRef<Path>>(dir: P) -> Result<Vec<PathBuf>> {
+  let mut pths = Vec::new();
+  let mut dirs = vec![dir.as_ref().to_path_buf()];
+  while let Some(dir) = dirs.pop() {
+    for ent in fs::read_dir(&dir).map_err(|e| anyhow!("{}: {}", dir.display(), e))? {
+      let pth = ent?.path();
+      if pth.is_dir() {
+        dirs.push(pth);
+      } else if pth.extension().map_or(false, |ext| ext == "bi5") {
+        pths.push(pth);
+      }
+    }
+  }
+  // Zero-padded directory and file names sort in hour order
+  pths.sort();
+  return Ok(pths);
+}
+
+/// `read_dir` reads all `.bi5` files within a directory tree in hour order.
+pub fn read_dir<P: AsRef<Path>>(dir: P, pnt: f64) -> Result<Vec<Tck>> {
+  let mut tcks = Vec::new();
+  for pth in pths(dir)? {
+    tcks.extend(read_path(&pth, pnt)?);
+  }
+  return Ok(tcks);
+}
+
+/// `load` reads all `.bi5` files within a directory tree into a new `TmeMli`.
+///
+/// Ticks outside the trading session are dropped.
+pub fn load<P: AsRef<Path>>(dir: P) -> Result<TmeMli> {
+  let mut tme = TmeMli::new();
+  // Prices are not stored, so any point size works
+  duk::insert(&mut tme, &read_dir(dir, 1.0)?)?;
+  return Ok(tme);
+}
+
+#[cfg(test)]
+mod tst {
+  use super::*;
+  use std::io::Write;
+
+  /// `enc` encodes ticks to raw records for the specified UTC hour.
+  fn enc(recs: &[(u32, u32, u32, f32, f32)]) -> Vec<u8> {
+    let mut raw = Vec::new();
+    for rec in recs {
+      raw.extend_from_slice(&rec.0.to_be_bytes());
+      raw.extend_from_slice(&rec.1.to_be_bytes());
+      raw.extend_from_slice(&rec.2.to_be_bytes());
+      raw.extend_from_slice(&rec.3.to_bits().to_be_bytes());
+      raw.extend_from_slice(&rec.4.to_bits().to_be_bytes());
+    }
+    return raw;
+  }
+
+  /// `wrt` writes LZMA compressed ticks to a `.bi5` file.
+  fn wrt(pth: &Path, recs: &[(u32, u32, u32, f32, f32)]) {
+    fs::create_dir_all(pth.parent().unwrap()).unwrap();
+    let raw = enc(recs);
+    let mut cmp = Vec::new();
+    lzma_rs::lzma_compress(&mut raw.as_slice(), &mut cmp).unwrap();
+    File::create(pth).unwrap().write_all(&cmp).unwrap();
+  }
+
+  #[test]
+  fn dcd_() {
+    let hr = Utc.ymd(2020, 5, 4).and_hms(13, 0, 0);
+    let raw = enc(&[(1_800_143, 1309632, 1307818, 0.0008, 0.0008), (3_599_999, 1309700, 1307900, 1.5, 2.0)]);
+    let tcks = dcd(&raw, hr, 0.001).unwrap();
+    assert_eq!(2, tcks.len());
+    // 13:30 UTC is 9:30am EDT
+    assert_eq!(NaiveDate::from_ymd(2020, 5, 4).and_hms_milli(9, 30, 0, 143), tcks[0].tme);
+    assert!((tcks[0].ask - 1309.632).abs() < 1e-9);
+    assert!((tcks[0].bid - 1307.818).abs() < 1e-9);
+    assert_eq!(0.0008f32 as f64, tcks[0].ask_vol);
+    assert_eq!(NaiveDate::from_ymd(2020, 5, 4).and_hms_milli(9, 59, 59, 999), tcks[1].tme);
+    assert_eq!(2.0, tcks[1].bid_vol);
+    assert!(dcd(&raw[..19], hr, 0.001).is_err());
+  }
+
+  #[test]
+  fn hr_of_path_() {
+    assert_eq!(Utc.ymd(2020, 5, 4).and_hms(13, 0, 0), hr_of_path(Path::new("GOOG/2020/04/04/13h_ticks.bi5")).unwrap());
+    assert_eq!(Utc.ymd(2020, 1, 31).and_hms(0, 0, 0), hr_of_path(Path::new("/dat/GOOG/2020/00/31/00h_ticks.bi5")).unwrap());
+    assert!(hr_of_path(Path::new("GOOG/2020/10/31/13h_ticks.bi5")).is_err());
+    assert!(hr_of_path(Path::new("13h_ticks.bi5")).is_err());
+  }
+
+  #[test]
+  fn load_dir() {
+    let dir = std::env::temp_dir().join(format!("tms_bi5_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    // Monday May 4th 2020 and Tuesday May 5th 2020, EDT is UTC-4
+    wrt(&dir.join("GOOG/2020/04/04/13h_ticks.bi5"), &[(1_799_999, 1, 1, 0.0, 0.0), (1_800_143, 1, 1, 0.0, 0.0), (1_800_193, 1, 1, 0.0, 0.0)]);
+    wrt(&dir.join("GOOG/2020/04/04/14h_ticks.bi5"), &[(0, 1, 1, 0.0, 0.0), (50, 1, 1, 0.0, 0.0)]);
+    wrt(&dir.join("GOOG/2020/04/05/19h_ticks.bi5"), &[(3_599_999, 1, 1, 0.0, 0.0)]);
+    // Hour without ticks
+    fs::create_dir_all(dir.join("GOOG/2020/04/05")).unwrap();
+    File::create(dir.join("GOOG/2020/04/05/20h_ticks.bi5")).unwrap();
+
+    let tme = load(&dir).unwrap();
+    let date1 = NaiveDate::from_ymd(2020, 5, 4);
+    let date2 = NaiveDate::from_ymd(2020, 5, 5);
+    assert_eq!(vec![
+      date1.and_hms_milli(9, 30, 0, 143),
+      date1.and_hms_milli(9, 30, 0, 193),
+      date1.and_hms_milli(10, 0, 0, 0),
+      date1.and_hms_milli(10, 0, 0, 50),
+    ], tme.get_day(date1).unwrap());
+    assert_eq!(vec![date2.and_hms_milli(15, 59, 59, 999)], tme.get_day(date2).unwrap());
+    fs::remove_dir_all(&dir).unwrap();
+  }
+}
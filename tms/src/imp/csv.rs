@@ -0,0 +1,289 @@
+//! `csv` module imports timestamps from vendor CSV files.
+//!
+//! `CsvImp` is configured with builder methods for the timestamp column, timestamp format,
+//! source timezone, and delimiter.
+//!
+//! ```ignore
+//! let tme = CsvImp::new()
+//!   .col_name("timestamp")
+//!   .epoch(EpochUnit::Mli)
+//!   .load("ticks.csv")?;
+//! ```
+
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use crate::TmeMli;
+use crate::imp;
+
+/// `EpochUnit` is the unit of an integer Unix epoch timestamp.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EpochUnit {
+  Sec,
+  Mli,
+  Mcr,
+  Nno,
+}
+
+/// `TmeFmt` is the format of a timestamp column.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TmeFmt {
+  /// `Iso8601` parses RFC 3339 timestamps with an offset, or ISO-8601 timestamps without an offset in the source timezone.
+  Iso8601,
+  /// `Fmt` parses timestamps with a chrono format string.
+  ///
+  /// A format with `%z` or `%:z` uses the parsed offset, otherwise the source timezone.
+  Fmt(String),
+  /// `Epoch` parses integer Unix epoch timestamps.
+  Epoch(EpochUnit),
+}
+
+/// `Col` selects the timestamp column.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Col {
+  Idx(usize),
+  Name(String),
+}
+
+/// `CsvImp` imports timestamps from CSV files.
+#[derive(Debug, Clone)]
+pub struct CsvImp {
+  col: Col,
+  fmt: TmeFmt,
+  tz: Tz,
+  dlm: u8,
+  hdr: bool,
+}
+
+impl Default for CsvImp {
+  /// `default` returns a `CsvImp` reading ISO-8601 timestamps from the first column of a comma delimited file with a header row.
+  ///
+  /// Timestamps without an offset are UTC.
+  fn default() -> CsvImp {
+    return CsvImp{
+      col: Col::Idx(0),
+      fmt: TmeFmt::Iso8601,
+      tz: Tz::UTC,
+      dlm: b',',
+      hdr: true,
+    };
+  }
+}
+
+impl CsvImp {
+  /// `new` returns the `default` `CsvImp`.
+  pub fn new() -> CsvImp {
+    return CsvImp::default();
+  }
+
+  /// `col_idx` selects the timestamp column by zero-based index.
+  pub fn col_idx(mut self, idx: usize) -> CsvImp {
+    self.col = Col::Idx(idx);
+    return self;
+  }
+
+  /// `col_name` selects the timestamp column by header name.
+  pub fn col_name(mut self, name: &str) -> CsvImp {
+    self.col = Col::Name(name.to_string());
+    return self;
+  }
+
+  /// `iso8601` parses ISO-8601 timestamps.
+  pub fn iso8601(mut self) -> CsvImp {
+    self.fmt = TmeFmt::Iso8601;
+    return self;
+  }
+
+  /// `fmt` parses timestamps with a chrono format string, such as `%Y-%m-%d %H:%M:%S%.f`.
+  pub fn fmt(mut self, fmt: &str) -> CsvImp {
+    self.fmt = TmeFmt::Fmt(fmt.to_string());
+    return self;
+  }
+
+  /// `epoch` parses integer Unix epoch timestamps in the specified unit.
+  pub fn epoch(mut self, unt: EpochUnit) -> CsvImp {
+    self.fmt = TmeFmt::Epoch(unt);
+    return self;
+  }
+
+  /// `tz` sets the source timezone of timestamps without an offset.
+  pub fn tz(mut self, tz: Tz) -> CsvImp {
+    self.tz = tz;
+    return self;
+  }
+
+  /// `delimiter` sets the field delimiter.
+  pub fn delimiter(mut self, dlm: u8) -> CsvImp {
+    self.dlm = dlm;
+    return self;
+  }
+
+  /// `has_headers` sets whether the first row is a header row.
+  pub fn has_headers(mut self, hdr: bool) -> CsvImp {
+    self.hdr = hdr;
+    return self;
+  }
+
+  /// `read` parses timestamps and returns them in the exchange timezone.
+  ///
+  /// A malformed row is an error reporting its line number.
+  pub fn read<R: Read>(&self, rdr: R) -> Result<Vec<NaiveDateTime>> {
+    let mut rdr = ::csv::ReaderBuilder::new()
+      .delimiter(self.dlm)
+      .has_headers(self.hdr)
+      .from_reader(rdr);
+    let col = match &self.col {
+      Col::Idx(idx) => *idx,
+      Col::Name(name) => {
+        if !self.hdr {
+          return Err(anyhow!("column {:?} requires a header row", name));
+        }
+        match rdr.headers()?.iter().position(|hdr| hdr.trim() == name) {
+          Some(idx) => idx,
+          None => return Err(anyhow!("line 1: column {:?} not found", name)),
+        }
+      },
+    };
+
+    let mut tmes = Vec::new();
+    for res in rdr.records() {
+      let rec = res?;
+      let lne = rec.position().map_or(0, |pos| pos.line());
+      let fld = match rec.get(col) {
+        Some(fld) => fld.trim(),
+        None => return Err(anyhow!("line {}: column {} not found", lne, col)),
+      };
+      let tme = self.prs(fld).map_err(|e| anyhow!("line {}: invalid time {:?}: {}", lne, fld, e))?;
+      tmes.push(tme);
+    }
+    return Ok(TmeMli::ndts_exc(&tmes));
+  }
+
+  /// `read_path` parses timestamps from a CSV file.
+  pub fn read_path<P: AsRef<Path>>(&self, pth: P) -> Result<Vec<NaiveDateTime>> {
+    let fle = File::open(pth.as_ref())
+      .map_err(|e| anyhow!("{}: {}", pth.as_ref().display(), e))?;
+    return self.read(fle);
+  }
+
+  /// `insert` parses timestamps and inserts each trading day into a `TmeMli`.
+  pub fn insert<R: Read>(&self, tme: &mut TmeMli, rdr: R) -> Result<()> {
+    return imp::insert_days(tme, &self.read(rdr)?);
+  }
+
+  /// `load` reads a CSV file into a new `TmeMli`.
+  pub fn load<P: AsRef<Path>>(&self, pth: P) -> Result<TmeMli> {
+    let mut tme = TmeMli::new();
+    imp::insert_days(&mut tme, &self.read_path(pth)?)?;
+    return Ok(tme);
+  }
+
+  /// `prs` parses a single timestamp to UTC.
+  fn prs(&self, fld: &str) -> Result<DateTime<Utc>> {
+    match &self.fmt {
+      TmeFmt::Iso8601 => {
+        if let Ok(tme) = DateTime::parse_from_rfc3339(fld) {
+          return Ok(tme.with_timezone(&Utc));
+        }
+        let ndt = NaiveDateTime::parse_from_str(fld, "%Y-%m-%dT%H:%M:%S%.f")
+          .or_else(|_| NaiveDateTime::parse_from_str(fld, "%Y-%m-%d %H:%M:%S%.f"))?;
+        return self.lcl(&ndt);
+      },
+      TmeFmt::Fmt(fmt) => {
+        if fmt.contains("%z") || fmt.contains("%:z") {
+          return Ok(DateTime::parse_from_str(fld, fmt)?.with_timezone(&Utc));
+        }
+        return self.lcl(&NaiveDateTime::parse_from_str(fld, fmt)?);
+      },
+      TmeFmt::Epoch(unt) => {
+        let v: i64 = fld.parse()?;
+        let (div, nno) = match unt {
+          EpochUnit::Sec => (1, 1_000_000_000),
+          EpochUnit::Mli => (1_000, 1_000_000),
+          EpochUnit::Mcr => (1_000_000, 1_000),
+          EpochUnit::Nno => (1_000_000_000, 1),
+        };
+        return match Utc.timestamp_opt(v.div_euclid(div), (v.rem_euclid(div) * nno) as u32).single() {
+          Some(tme) => Ok(tme),
+          None => Err(anyhow!("epoch is out of range")),
+        };
+      },
+    }
+  }
+
+  /// `lcl` converts a local time in the source timezone to UTC.
+  ///
+  /// An ambiguous time during a daylight saving time transition uses the earliest time.
+  fn lcl(&self, ndt: &NaiveDateTime) -> Result<DateTime<Utc>> {
+    return match self.tz.from_local_datetime(ndt).earliest() {
+      Some(tme) => Ok(tme.with_timezone(&Utc)),
+      None => Err(anyhow!("time does not exist in {}", self.tz.name())),
+    };
+  }
+}
+
+#[cfg(test)]
+mod tst {
+  use super::*;
+  use chrono::NaiveDate;
+
+  #[test]
+  fn read_epoch_mli() {
+    // 2020-05-04 13:30:00.143 UTC is 9:30am EDT
+    let csv = "sym,ts,px\nGOOG,1588599000143,1309.6\nGOOG,1588599000193,1309.7\n";
+    let tmes = CsvImp::new().col_name("ts").epoch(EpochUnit::Mli).read(csv.as_bytes()).unwrap();
+    let date = NaiveDate::from_ymd(2020, 5, 4);
+    assert_eq!(vec![date.and_hms_milli(9, 30, 0, 143), date.and_hms_milli(9, 30, 0, 193)], tmes);
+    let tmes = CsvImp::new().col_idx(1).epoch(EpochUnit::Nno).read("sym,ts\nGOOG,1588599000143000000\n".as_bytes()).unwrap();
+    assert_eq!(vec![date.and_hms_milli(9, 30, 0, 143)], tmes);
+    let tmes = CsvImp::new().epoch(EpochUnit::Sec).has_headers(false).read("1588599000\n".as_bytes()).unwrap();
+    assert_eq!(vec![date.and_hms(9, 30, 0)], tmes);
+  }
+
+  #[test]
+  fn read_iso8601() {
+    let csv = "time\n2020-05-04T13:30:00.143Z\n2020-05-04T09:30:00.193-04:00\n2020-05-04T13:30:00.250\n";
+    let tmes = CsvImp::new().read(csv.as_bytes()).unwrap();
+    let date = NaiveDate::from_ymd(2020, 5, 4);
+    assert_eq!(vec![date.and_hms_milli(9, 30, 0, 143), date.and_hms_milli(9, 30, 0, 193), date.and_hms_milli(9, 30, 0, 250)], tmes);
+  }
+
+  #[test]
+  fn read_fmt_tz() {
+    // Pacific time in summer and winter
+    let csv = "px;time\n1.0;2020-05-04 06:30:00.143\n1.0;2020-11-09 06:30:00.000\n";
+    let tmes = CsvImp::new()
+      .col_name("time")
+      .fmt("%Y-%m-%d %H:%M:%S%.f")
+      .tz(chrono_tz::America::Los_Angeles)
+      .delimiter(b';')
+      .read(csv.as_bytes()).unwrap();
+    assert_eq!(vec![NaiveDate::from_ymd(2020, 5, 4).and_hms_milli(9, 30, 0, 143), NaiveDate::from_ymd(2020, 11, 9).and_hms(9, 30, 0)], tmes);
+  }
+
+  #[test]
+  fn read_err_lne() {
+    let csv = "time\n1588599000143\nabc\n";
+    let err = CsvImp::new().epoch(EpochUnit::Mli).read(csv.as_bytes()).unwrap_err().to_string();
+    assert!(err.starts_with("line 3:"), "{}", err);
+    let csv_rng = "time\n1588599000143\n9223372036854775807\n";
+    let err = CsvImp::new().epoch(EpochUnit::Mli).read(csv_rng.as_bytes()).unwrap_err().to_string();
+    assert!(err.starts_with("line 3:") && err.contains("out of range"), "{}", err);
+    let err = CsvImp::new().col_name("ts").read(csv.as_bytes()).unwrap_err().to_string();
+    assert!(err.contains("\"ts\""), "{}", err);
+  }
+
+  #[test]
+  fn insert_() {
+    let csv = "ts\n1588685400000\n1588598000000\n1588599000143\n1588599000193\n";
+    let mut tme = TmeMli::new();
+    CsvImp::new().epoch(EpochUnit::Mli).insert(&mut tme, csv.as_bytes()).unwrap();
+    let date1 = NaiveDate::from_ymd(2020, 5, 4);
+    let date2 = NaiveDate::from_ymd(2020, 5, 5);
+    assert_eq!(vec![date1.and_hms_milli(9, 30, 0, 143), date1.and_hms_milli(9, 30, 0, 193)], tme.get_day(date1).unwrap());
+    assert_eq!(vec![date2.and_hms(9, 30, 0)], tme.get_day(date2).unwrap());
+  }
+}
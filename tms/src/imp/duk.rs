@@ -0,0 +1,168 @@
+//! `duk` module imports Dukascopy tick CSV files.
+//!
+//! Dukascopy tick CSV exports are laid out as:
+//!
+//! ```text
+//! Local time,Ask,Bid,AskVolume,BidVolume
+//! 04.05.2020 06:30:00.143 GMT-0700,1309.632,1307.818,0.0008,0.0008
+//! 04.05.2020 06:30:00.193 GMT-0700,1309.632,1307.838,0.002,0.002
+//! ```
+//!
+//! `Local time` is Day.Month.Year in the local timezone of the export with its UTC offset.
+//!
+//! See Dukascopy historical data https://www.dukascopy.com/swiss/english/marketwatch/historical/.
+
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, NaiveDateTime};
+use crate::TmeMli;
+use crate::imp;
+
+/// `TME_HDR` is the name of the first column, which identifies a Dukascopy tick CSV.
+pub const TME_HDR: &str = "Local time";
+
+/// `TME_PRS_FMT` is the `Local time` parsing format.
+pub const TME_PRS_FMT: &str = "%d.%m.%Y %H:%M:%S%.3f GMT%z";
+
+/// `Tck` is a Dukascopy tick.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tck {
+  /// `tme` is the tick time in the exchange timezone.
+  pub tme: NaiveDateTime,
+  pub ask: f64,
+  pub bid: f64,
+  pub ask_vol: f64,
+  pub bid_vol: f64,
+}
+
+/// `read` parses Dukascopy ticks from CSV with a header row.
+///
+/// Times are converted from their UTC offset to the exchange timezone.
+///
+/// A malformed row is an error reporting its line number.
+pub fn read<R: Read>(rdr: R) -> Result<Vec<Tck>> {
+  let mut rdr = csv::Reader::from_reader(rdr);
+  let mut tcks = Vec::new();
+  for res in rdr.records() {
+    let rec = res?;
+    let lne = rec.position().map_or(0, |pos| pos.line());
+    if rec.len() != 5 {
+      return Err(anyhow!("line {}: expected 5 columns, found {}", lne, rec.len()));
+    }
+    let tme = DateTime::parse_from_str(&rec[0], TME_PRS_FMT)
+      .map_err(|e| anyhow!("line {}: invalid time {:?}: {}", lne, &rec[0], e))?;
+    let mut vals = [0f64; 4];
+    for n in 0..vals.len() {
+      vals[n] = rec[n+1].trim().parse::<f64>()
+        .map_err(|e| anyhow!("line {}: invalid value {:?}: {}", lne, &rec[n+1], e))?;
+    }
+    tcks.push(Tck{
+      tme: TmeMli::ndts_exc(&[tme])[0],
+      ask: vals[0],
+      bid: vals[1],
+      ask_vol: vals[2],
+      bid_vol: vals[3],
+    });
+  }
+  return Ok(tcks);
+}
+
+/// `read_path` parses Dukascopy ticks from a CSV file.
+pub fn read_path<P: AsRef<Path>>(pth: P) -> Result<Vec<Tck>> {
+  let fle = File::open(pth.as_ref())
+    .map_err(|e| anyhow!("{}: {}", pth.as_ref().display(), e))?;
+  return read(fle);
+}
+
+/// `split_days` splits ticks into consecutive runs with the same exchange date.
+pub fn split_days(tcks: &[Tck]) -> Vec<&[Tck]> {
+  return imp::split_days(tcks, |tck| tck.tme.date());
+}
+
+/// `tmes` returns the times of ticks.
+pub fn tmes(tcks: &[Tck]) -> Vec<NaiveDateTime> {
+  return tcks.iter().map(|tck| tck.tme).collect();
+}
+
+/// `insert` inserts the ticks of each trading day into a `TmeMli`.
+///
+/// Ticks outside the trading session are dropped. Days may be in any order.
+pub fn insert(tme: &mut TmeMli, tcks: &[Tck]) -> Result<()> {
+  return imp::insert_days(tme, &tmes(tcks));
+}
+
+/// `load` reads a Dukascopy tick CSV file into a new `TmeMli`.
+pub fn load<P: AsRef<Path>>(pth: P) -> Result<TmeMli> {
+  let mut tme = TmeMli::new();
+  insert(&mut tme, &read_path(pth)?)?;
+  return Ok(tme);
+}
+
+#[cfg(test)]
+mod tst {
+  use super::*;
+  use chrono::NaiveDate;
+
+  const CSV: &str = "Local time,Ask,Bid,AskVolume,BidVolume
+04.05.2020 06:29:59.900 GMT-0700,1309.632,1307.818,0.0008,0.0008
+04.05.2020 06:30:00.143 GMT-0700,1309.632,1307.818,0.0008,0.0008
+04.05.2020 06:30:00.193 GMT-0700,1309.632,1307.838,0.002,0.002
+05.05.2020 12:59:59.999 GMT-0700,1310.5,1309.5,0.001,0.003
+09.11.2020 06:30:00.000 GMT-0800,1750.25,1749.75,0.01,0.02
+";
+
+  #[test]
+  fn read_() {
+    let tcks = read(CSV.as_bytes()).unwrap();
+    assert_eq!(5, tcks.len());
+    assert_eq!(NaiveDate::from_ymd(2020, 5, 4).and_hms_milli(9, 30, 0, 143), tcks[1].tme);
+    assert_eq!(1309.632, tcks[1].ask);
+    assert_eq!(1307.838, tcks[2].bid);
+    assert_eq!(0.001, tcks[3].ask_vol);
+    assert_eq!(0.003, tcks[3].bid_vol);
+    // PST is also 3 hours behind ET
+    assert_eq!(NaiveDate::from_ymd(2020, 11, 9).and_hms(9, 30, 0), tcks[4].tme);
+  }
+
+  #[test]
+  fn read_err_lne() {
+    let csv = "Local time,Ask,Bid,AskVolume,BidVolume
+04.05.2020 06:30:00.143 GMT-0700,1309.632,1307.818,0.0008,0.0008
+04.05.2020 06:30:00.193,1309.632,1307.838,0.002,0.002
+";
+    let err = read(csv.as_bytes()).unwrap_err().to_string();
+    assert!(err.starts_with("line 3:"), "{}", err);
+
+    let csv = "Local time,Ask,Bid,AskVolume,BidVolume
+04.05.2020 06:30:00.143 GMT-0700,1309.632,1307.818,0.0008,0.0008
+04.05.2020 06:30:00.193 GMT-0700,1309.632,1307.838,0.002,0.002
+04.05.2020 06:30:00.293 GMT-0700,1309.632,abc,0.002,0.002
+";
+    let err = read(csv.as_bytes()).unwrap_err().to_string();
+    assert!(err.starts_with("line 4:"), "{}", err);
+  }
+
+  #[test]
+  fn split_days_() {
+    let tcks = read(CSV.as_bytes()).unwrap();
+    let days = split_days(&tcks);
+    assert_eq!(3, days.len());
+    assert_eq!(3, days[0].len());
+    assert_eq!(1, days[1].len());
+    assert_eq!(1, days[2].len());
+    assert_eq!(0, split_days(&[]).len());
+  }
+
+  #[test]
+  fn insert_() {
+    let tcks = read(CSV.as_bytes()).unwrap();
+    let mut tme = TmeMli::new();
+    insert(&mut tme, &tcks).unwrap();
+    // Tick before 9:30am ET is dropped
+    assert_eq!(tmes(&tcks[1..3]), tme.get_day(NaiveDate::from_ymd(2020, 5, 4)).unwrap());
+    assert_eq!(tmes(&tcks[3..4]), tme.get_day(NaiveDate::from_ymd(2020, 5, 5)).unwrap());
+    assert_eq!(tmes(&tcks[4..5]), tme.get_day(NaiveDate::from_ymd(2020, 11, 9)).unwrap());
+  }
+}
@@ -0,0 +1,32 @@
+//! `imp` module imports market data from vendor files.
+
+use chrono::{NaiveDate, NaiveDateTime};
+use anyhow::Result;
+use crate::TmeMli;
+
+pub mod bi5;
+pub mod csv;
+pub mod duk;
+
+/// `split_days` splits items into consecutive runs with the same exchange date.
+pub fn split_days<T, F: Fn(&T) -> NaiveDate>(itms: &[T], date: F) -> Vec<&[T]> {
+  let mut days = Vec::new();
+  let mut idx: usize = 0;
+  for n in 1..=itms.len() {
+    if n == itms.len() || date(&itms[n]) != date(&itms[idx]) {
+      days.push(&itms[idx..n]);
+      idx = n;
+    }
+  }
+  return days;
+}
+
+/// `insert_days` inserts exchange times of one or more trading days into a `TmeMli`.
+///
+/// Times outside the trading session are dropped. Days may be in any order.
+pub fn insert_days(tme: &mut TmeMli, tmes: &[NaiveDateTime]) -> Result<()> {
+  for day in split_days(tmes, |tme| tme.date()) {
+    tme.insert_day(day)?;
+  }
+  return Ok(());
+}
@@ -0,0 +1,367 @@
+//! `tms` command-line tool packs, inspects, and unpacks `TmeMli` archives.
+//!
+//! An archive is a `TmeMli` buffer written to a file.
+
+use std::env;
+use std::fs::{self, File};
+use std::hint::black_box;
+use std::io::{BufRead, BufReader, BufWriter};
+use std::path::Path;
+use std::process;
+use std::time::Instant;
+use anyhow::{anyhow, bail, Result};
+use chrono::NaiveDate;
+use chrono_tz::Tz;
+use tms::{day_u32x256_hdr, PckOpt, TmeMli};
+use tms::exp::Exp;
+use tms::sts::LEN_BIT_HST;
+use tms::imp::{self, bi5, duk};
+use tms::imp::csv::{CsvImp, EpochUnit};
+
+const USAGE: &str = "usage: tms <command> [options] <paths>
+
+commands:
+  pack <archive> <input>...   pack CSV files, .bi5 files, or .bi5 directories into an archive
+  unpack <archive> <csv>      unpack an archive to CSV
+  info <archive>              print the format version, pack options, day count, first and last day, and ratio
+  days <archive>              list days with tick counts and bytes
+  verify <archive>            fully decode and check every day
+  stats <input>               print compression statistics per day and block bit-length histogram
+  bench <input>               measure ratio and throughput of an archive or input file
+
+options:
+  --col <name|idx>            CSV timestamp column
+  --fmt <fmt>                 CSV chrono timestamp format
+  --epoch <s|ms|us|ns>        CSV Unix epoch timestamps
+  --tz <tz>                   CSV timestamp timezone, such as UTC or America/New_York
+  --delim <char>              CSV delimiter
+  --from <yyyy-mm-dd>         first day to unpack
+  --to <yyyy-mm-dd>           last day to unpack
+  --iters <n>                 bench iterations
+  --bse                       pack a base value for each block, so blocks decode independently
+  --exc                       pack blocks at narrower bit-lengths with exceptions
+  --svb                       pack the values after the last block as Stream VByte
+
+CSV files without --col, --fmt, --epoch, --tz, or --delim are read as Dukascopy tick CSV when the first column is
+\"Local time\", and otherwise as timestamps in the first column, such as the RFC 3339 timestamps written by unpack.";
+
+/// `Opts` are parsed command-line options and positional arguments.
+#[derive(Debug, Default)]
+struct Opts {
+  pos: Vec<String>,
+  col: Option<String>,
+  fmt: Option<String>,
+  epoch: Option<EpochUnit>,
+  tz: Option<Tz>,
+  dlm: Option<u8>,
+  fst: Option<NaiveDate>,
+  lst: Option<NaiveDate>,
+  itr: Option<usize>,
+  opt: PckOpt,
+}
+
+impl Opts {
+  /// `parse` parses options and positional arguments.
+  fn parse(args: &[String]) -> Result<Opts> {
+    let mut opts = Opts::default();
+    let mut itr = args.iter();
+    while let Some(arg) = itr.next() {
+      if !arg.starts_with("--") {
+        opts.pos.push(arg.clone());
+        continue;
+      }
+      if opts.flag(arg) {
+        continue;
+      }
+      let val = itr.next().ok_or_else(|| anyhow!("{} requires a value", arg))?;
+      match arg.as_str() {
+        "--col" => opts.col = Some(val.clone()),
+        "--fmt" => opts.fmt = Some(val.clone()),
+        "--epoch" => opts.epoch = Some(match val.as_str() {
+          "s" => EpochUnit::Sec,
+          "ms" => EpochUnit::Mli,
+          "us" => EpochUnit::Mcr,
+          "ns" => EpochUnit::Nno,
+          _ => bail!("--epoch {}: expected s, ms, us, or ns", val),
+        }),
+        "--tz" => opts.tz = Some(val.parse().map_err(|e| anyhow!("--tz {}: {}", val, e))?),
+        "--delim" => opts.dlm = match val.as_bytes() {
+          [dlm] => Some(*dlm),
+          _ => bail!("--delim {}: expected a single byte", val),
+        },
+        "--from" => opts.fst = Some(NaiveDate::parse_from_str(val, "%Y-%m-%d").map_err(|e| anyhow!("--from {}: {}", val, e))?),
+        "--to" => opts.lst = Some(NaiveDate::parse_from_str(val, "%Y-%m-%d").map_err(|e| anyhow!("--to {}: {}", val, e))?),
+        "--iters" => opts.itr = Some(val.parse().map_err(|e| anyhow!("--iters {}: {}", val, e))?),
+        _ => bail!("unknown option {}", arg),
+      }
+    }
+    return Ok(opts);
+  }
+
+  /// `flag` sets an option which takes no value, and returns false when `arg` isn't a flag.
+  fn flag(&mut self, arg: &str) -> bool {
+    match arg {
+      "--bse" => self.opt.bse = true,
+      "--exc" => self.opt.exc = true,
+      "--svb" => self.opt.svb = true,
+      _ => return false,
+    }
+    return true;
+  }
+
+  /// `pos` returns the positional argument at `idx`.
+  fn pos(&self, idx: usize, name: &str) -> Result<&str> {
+    return self.pos.get(idx).map(|s| s.as_str()).ok_or_else(|| anyhow!("missing <{}>\n\n{}", name, USAGE));
+  }
+
+  /// `csv_imp` returns a generic CSV importer when any CSV option is set.
+  fn csv_imp(&self) -> Option<CsvImp> {
+    if self.col.is_none() && self.fmt.is_none() && self.epoch.is_none() && self.tz.is_none() && self.dlm.is_none() {
+      return None;
+    }
+    let mut imp = CsvImp::new();
+    if let Some(col) = &self.col {
+      imp = match col.parse::<usize>() {
+        Ok(idx) => imp.col_idx(idx),
+        Err(_) => imp.col_name(col),
+      };
+    }
+    if let Some(fmt) = &self.fmt {
+      imp = imp.fmt(fmt);
+    }
+    if let Some(unt) = self.epoch {
+      imp = imp.epoch(unt);
+    }
+    if let Some(tz) = self.tz {
+      imp = imp.tz(tz);
+    }
+    if let Some(dlm) = self.dlm {
+      imp = imp.delimiter(dlm);
+    }
+    return Some(imp);
+  }
+}
+
+fn main() {
+  let args: Vec<String> = env::args().skip(1).collect();
+  if let Err(e) = run(&args) {
+    eprintln!("tms: {:#}", e);
+    process::exit(1);
+  }
+}
+
+/// `run` runs a command.
+fn run(args: &[String]) -> Result<()> {
+  let (cmd, args) = match args.split_first() {
+    Some(split) => split,
+    None => bail!("missing <command>\n\n{}", USAGE),
+  };
+  let opts = Opts::parse(args)?;
+  return match cmd.as_str() {
+    "pack" => pack(&opts),
+    "unpack" => unpack(&opts),
+    "info" => info(&opts),
+    "days" => days(&opts),
+    "verify" => verify(&opts),
+    "stats" => stats(&opts),
+    "bench" => bench(&opts),
+    "help" | "-h" | "--help" => {
+      println!("{}", USAGE);
+      Ok(())
+    },
+    _ => bail!("unknown command {:?}\n\n{}", cmd, USAGE),
+  };
+}
+
+/// `read_archive` reads an archive file.
+fn read_archive(pth: &str) -> Result<TmeMli> {
+  let buf = fs::read(pth).map_err(|e| anyhow!("{}: {}", pth, e))?;
+  return TmeMli::from_buf(buf).map_err(|e| anyhow!("{}: {}", pth, e));
+}
+
+/// `insert_input` inserts a CSV file, `.bi5` file, or directory of `.bi5` files into a `TmeMli`.
+fn insert_input(tme: &mut TmeMli, pth: &str, opts: &Opts) -> Result<()> {
+  let pth = Path::new(pth);
+  if pth.is_dir() {
+    // Prices are not stored, so any point size works
+    return duk::insert(tme, &bi5::read_dir(pth, 1.0)?);
+  }
+  return match pth.extension().and_then(|ext| ext.to_str()) {
+    Some("bi5") => duk::insert(tme, &bi5::read_path(pth, 1.0)?),
+    Some("csv") | Some("txt") => match opts.csv_imp() {
+      Some(csv_imp) => imp::insert_days(tme, &csv_imp.read_path(pth)?),
+      None if is_duk(pth)? => duk::insert(tme, &duk::read_path(pth)?),
+      // Read timestamps in the first column, such as the default output of `unpack`
+      None => imp::insert_days(tme, &CsvImp::new().read_path(pth)?),
+    },
+    _ => bail!("{}: expected a .csv file, .bi5 file, or directory", pth.display()),
+  };
+}
+
+/// `is_duk` returns true when the first column of a CSV file is the Dukascopy `Local time`.
+fn is_duk(pth: &Path) -> Result<bool> {
+  let fle = File::open(pth).map_err(|e| anyhow!("{}: {}", pth.display(), e))?;
+  let mut hdr = String::new();
+  BufReader::new(fle).read_line(&mut hdr).map_err(|e| anyhow!("{}: {}", pth.display(), e))?;
+  return Ok(hdr.trim_start_matches('\u{feff}').starts_with(duk::TME_HDR));
+}
+
+/// `load_input` reads an archive or input file.
+fn load_input(pth: &str, opts: &Opts) -> Result<TmeMli> {
+  if Path::new(pth).extension().map_or(false, |ext| ext == "tms") {
+    return read_archive(pth);
+  }
+  let mut tme = TmeMli::new();
+  insert_input(&mut tme, pth, opts)?;
+  return Ok(tme);
+}
+
+/// `pack` packs input files into an archive.
+fn pack(opts: &Opts) -> Result<()> {
+  let out = opts.pos(0, "archive")?;
+  opts.pos(1, "input")?;
+  let mut tme = TmeMli::new();
+  tme.opt = opts.opt;
+  for pth in &opts.pos[1..] {
+    insert_input(&mut tme, pth, opts)?;
+  }
+  fs::write(out, &tme.buf).map_err(|e| anyhow!("{}: {}", out, e))?;
+  println!("packed {} ticks in {} days to {} bytes", tme.len_ticks(), tme.days().count(), tme.buf.len());
+  return Ok(());
+}
+
+/// `unpack` writes an archive to CSV.
+fn unpack(opts: &Opts) -> Result<()> {
+  let tme = read_archive(opts.pos(0, "archive")?)?;
+  let out = opts.pos(1, "csv")?;
+  let (fst, lst) = match (tme.first_day(), tme.last_day()) {
+    (Some(fst), Some(lst)) => (opts.fst.unwrap_or(fst), opts.lst.unwrap_or(lst)),
+    _ => (NaiveDate::MIN, NaiveDate::MIN),
+  };
+  let mut exp = Exp::new();
+  if let Some(fmt) = &opts.fmt {
+    exp = exp.fmt(fmt);
+  }
+  if let Some(unt) = opts.epoch {
+    exp = exp.epoch(unt);
+  }
+  if let Some(tz) = opts.tz {
+    exp = exp.tz(tz);
+  }
+  if let Some(dlm) = opts.dlm {
+    exp = exp.delimiter(dlm);
+  }
+  let fle = File::create(out).map_err(|e| anyhow!("{}: {}", out, e))?;
+  return exp.write_csv(&tme, fst, lst, BufWriter::new(fle));
+}
+
+/// `info` prints an archive summary.
+fn info(opts: &Opts) -> Result<()> {
+  let tme = read_archive(opts.pos(0, "archive")?)?;
+  let fmt_day = |day: Option<NaiveDate>| day.map_or("-".to_string(), |day| day.to_string());
+  let tck_cnt = tme.len_ticks();
+  println!("format:      version {}", tme.fmt_ver());
+  // Options aren't stored per archive, so count the options of each day's headers
+  let mut opt_cnts: Vec<(PckOpt, usize)> = Vec::new();
+  for (_, day) in &tme {
+    let opt = match day_u32x256_hdr(day.pck) {
+      Some(hdr) => hdr.opt(),
+      None => continue,
+    };
+    match opt_cnts.iter_mut().find(|(prv, _)| *prv == opt) {
+      Some((_, cnt)) => *cnt += 1,
+      None => opt_cnts.push((opt, 1)),
+    }
+  }
+  for (opt, cnt) in &opt_cnts {
+    println!("pack opts:   bse={} exc={} svb={} ({} days)", opt.bse, opt.exc, opt.svb, cnt);
+  }
+  println!("first day:   {}", fmt_day(tme.first_day()));
+  println!("last day:    {}", fmt_day(tme.last_day()));
+  println!("day span:    {}", tme.day_span());
+  println!("stored days: {}", tme.day_count());
+  println!("ticks:       {}", tck_cnt);
+  println!("bytes:       {}", tme.buf.len());
+  // Ratio against 12-byte NaiveDateTimes
+  println!("ratio:       {:.2}", (tck_cnt * 12) as f64 / tme.buf.len() as f64);
+  return Ok(());
+}
+
+/// `days` lists stored days.
+fn days(opts: &Opts) -> Result<()> {
+  let tme = read_archive(opts.pos(0, "archive")?)?;
+  println!("date,ticks,bytes");
+  for (date, day) in &tme {
+    println!("{},{},{}", date, day.len(), day.pck.len());
+  }
+  return Ok(());
+}
+
+/// `verify` fully decodes an archive.
+fn verify(opts: &Opts) -> Result<()> {
+  let pth = opts.pos(0, "archive")?;
+  let tme = read_archive(pth)?;
+  tme.verify().map_err(|e| anyhow!("{}: {}", pth, e))?;
+  println!("verified {} ticks in {} days", tme.len_ticks(), tme.days().count());
+  return Ok(());
+}
+
+/// `stats` prints compression statistics per day, then totals and the block bit-length histogram.
+fn stats(opts: &Opts) -> Result<()> {
+  let sts = load_input(opts.pos(0, "input")?, opts)?.stats();
+  println!("date,ticks,bytes,bits_per_tick,tail_frac,ratio_ndt,ratio_i64,blocks");
+  for day in &sts.days {
+    println!("{},{},{},{:.2},{:.3},{:.2},{:.2},{}", day.date, day.ticks, day.byt_len, day.bits_per_tick(),
+      day.tal_frc(), day.ratio_ndt(), day.ratio_i64(), day.blk_cnt());
+  }
+  println!();
+  println!("ticks:         {}", sts.ticks());
+  println!("bytes:         {}", sts.byt_len);
+  println!("bits per tick: {:.2}", sts.bits_per_tick());
+  println!("tail fraction: {:.3}", sts.tal_frc());
+  println!("ratio ndt:     {:.2} (12-byte NaiveDateTime)", sts.ratio_ndt());
+  println!("ratio i64:     {:.2} (8-byte i64)", sts.ratio_i64());
+  println!();
+  println!("bit_len,blocks");
+  let hst = sts.blk_bit_hst();
+  for bit_len in 0..LEN_BIT_HST {
+    if hst[bit_len] != 0 {
+      println!("{},{}", bit_len, hst[bit_len]);
+    }
+  }
+  return Ok(());
+}
+
+/// `bench` measures the compression ratio and decode and encode throughput.
+fn bench(opts: &Opts) -> Result<()> {
+  let tme = load_input(opts.pos(0, "input")?, opts)?;
+  let itr = opts.itr.unwrap_or(10).max(1);
+  let tck_cnt = tme.len_ticks();
+  let days: Vec<_> = tme.days().map(|(_, day)| day.ndts()).collect();
+
+  let tme_dcd = Instant::now();
+  for _ in 0..itr {
+    for (_, day) in &tme {
+      black_box(day.u32s());
+    }
+  }
+  let dur_dcd = tme_dcd.elapsed().as_secs_f64();
+
+  let tme_ecd = Instant::now();
+  for _ in 0..itr {
+    let mut act = TmeMli::new();
+    for day in &days {
+      act.append_day(day)?;
+    }
+    black_box(act);
+  }
+  let dur_ecd = tme_ecd.elapsed().as_secs_f64();
+
+  let mtck = (tck_cnt * itr) as f64 / 1e6;
+  println!("ticks:  {}", tck_cnt);
+  println!("bytes:  {}", tme.buf.len());
+  println!("ratio:  {:.2}", (tck_cnt * 12) as f64 / tme.buf.len() as f64);
+  println!("decode: {:.1} Mticks/s", mtck / dur_dcd);
+  println!("encode: {:.1} Mticks/s", mtck / dur_ecd);
+  return Ok(());
+}
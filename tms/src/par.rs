@@ -0,0 +1,193 @@
+//! `par` module compresses and decompresses many days in parallel with rayon.
+//!
+//! Days are packed independently, each with its own `Day Ref Idx` and header, so each day is a unit of work.
+
+use std::borrow::Cow;
+use std::ops::RangeBounds;
+use anyhow::{bail, Result};
+use chrono::{NaiveDate, NaiveDateTime};
+use rayon::prelude::*;
+use crate::{day_u32x256_chk, day_u32x256_unp, DayRef, PckOpt, TmeMli};
+
+/// `DaysUnp` are decompressed days in one contiguous u32 buffer.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DaysUnp {
+  /// `dates` are the dates of stored days in date order.
+  pub dates: Vec<NaiveDate>,
+  /// `offs` are the start index of each day in `vals`, followed by the length of `vals`.
+  pub offs: Vec<usize>,
+  /// `vals` are u32 milliseconds from 9:30am ET.
+  pub vals: Vec<u32>,
+}
+
+impl DaysUnp {
+  /// `len` returns the number of days.
+  pub fn len(&self) -> usize {
+    return self.dates.len();
+  }
+
+  /// `is_empty` returns true when there are no days.
+  pub fn is_empty(&self) -> bool {
+    return self.dates.len() == 0;
+  }
+
+  /// `day` returns the values of the day at the specified position.
+  pub fn day(&self, idx: usize) -> &[u32] {
+    return &self.vals[self.offs[idx]..self.offs[idx + 1]];
+  }
+}
+
+impl TmeMli {
+  /// `decode_days_par` decompresses stored days within a date range across threads.
+  pub fn decode_days_par<R: RangeBounds<NaiveDate>>(&self, rng: R) -> DaysUnp {
+    let mut out = DaysUnp::default();
+    self.decode_days_par_into(rng, &mut out);
+    return out;
+  }
+
+  /// `decode_days_par_into` decompresses stored days within a date range across threads, reusing the buffers of `out`.
+  /// 
+  /// Value counts are read from day headers to preallocate `out.vals`, which is then split into one slice per day.
+  /// Days which fail `day_u32x256_chk` are skipped.
+  pub fn decode_days_par_into<R: RangeBounds<NaiveDate>>(&self, rng: R, out: &mut DaysUnp) {
+    let days: Vec<DayRef> = self.days()
+      .filter(|(date, day)| rng.contains(date) && day_u32x256_chk(day.pck))
+      .map(|(_, day)| day)
+      .collect();
+    out.dates.clear();
+    out.dates.extend(days.iter().map(|day| day.date));
+    out.offs.clear();
+    out.offs.push(0);
+    for day in &days {
+      out.offs.push(out.offs[out.offs.len() - 1] + day.len());
+    }
+    out.vals.clear();
+    out.vals.resize(out.offs[days.len()], 0);
+
+    // Split the output into disjoint per-day slices
+    let mut unps: Vec<&mut [u32]> = Vec::with_capacity(days.len());
+    let mut rem = out.vals.as_mut_slice();
+    for day in &days {
+      let (unp, tal) = rem.split_at_mut(day.len());
+      unps.push(unp);
+      rem = tal;
+    }
+    days.par_iter().zip(unps.into_par_iter()).for_each(|(day, unp)| {
+      day_u32x256_unp(day.pck, unp);
+    });
+  }
+
+  /// `append_days_par` appends many days, compressing them across threads, and then stitches the buffer together.
+  /// 
+  /// Each day is handled as with `append_day`: days must be in date order and after the last stored day.
+  /// Nothing is appended when any day is an error.
+  pub fn append_days_par<T: AsRef<[NaiveDateTime]> + Sync>(&mut self, days: &[T]) -> Result<()> {
+    // Prepare and compress each day
+    let pcks: Vec<Result<Option<(NaiveDate, Vec<u8>)>>> = days.par_iter().map(|day| {
+      let day = self.day_prp(day.as_ref())?;
+      if day.len() == 0 {
+        return Ok(None);
+      }
+      return Ok(Some((day[0].date(), TmeMli::day_pck_new(&day, self.opt))));
+    }).collect();
+    let mut new = Vec::with_capacity(pcks.len());
+    for pck in pcks {
+      if let Some(pck) = pck? {
+        new.push(pck);
+      }
+    }
+    if new.len() == 0 {
+      return Ok(());
+    }
+
+    // Validate date order
+    let mut date_prv = self.last_day();
+    for (date, _) in &new {
+      if let Some(date_prv) = date_prv {
+        if *date <= date_prv {
+          bail!("day {} is not after the last day {}", date, date_prv);
+        }
+      }
+      date_prv = Some(*date);
+    }
+
+    // Stitch stored days, days without values for gaps, and new days
+    let day_fst = self.first_day().unwrap_or(new[0].0);
+    let pck_emp = TmeMli::day_pck_new(&[], PckOpt::default());
+    let mut all: Vec<Cow<[u8]>> = (0..self.day_span()).map(|n| Cow::Borrowed(self.day_pck(n))).collect();
+    // New days are after the last day, so only a date after the last new day may saturate at `NaiveDate::MAX`
+    let mut date_nxt = self.last_day().map_or(day_fst, |date| date.succ_opt().unwrap_or(NaiveDate::MAX));
+    for (date, pck) in &new {
+      while date_nxt < *date {
+        all.push(Cow::Borrowed(&pck_emp));
+        date_nxt = date_nxt.succ_opt().unwrap_or(NaiveDate::MAX);
+      }
+      all.push(Cow::Borrowed(pck));
+      date_nxt = date.succ_opt().unwrap_or(NaiveDate::MAX);
+    }
+    let tme = TmeMli::from_day_pcks(day_fst, &all);
+    self.buf = tme.buf;
+    return Ok(());
+  }
+}
+
+#[cfg(test)]
+mod tst {
+  use super::*;
+  use crate::tst::goog_tmes;
+
+  fn days(cnt: usize) -> Vec<Vec<NaiveDateTime>> {
+    let mut date = NaiveDate::from_ymd(2020, 5, 4);
+    let mut days = Vec::new();
+    for n in 0..cnt {
+      // Skip a day every third day to leave gaps
+      let day = goog_tmes(date);
+      days.push(day[..day.len() - (n * 97)].to_vec());
+      date = date.succ();
+      if n % 3 == 2 {
+        date = date.succ();
+      }
+    }
+    return days;
+  }
+
+  #[test]
+  fn append_days_par_() {
+    let days = days(10);
+    let mut exp = TmeMli::new();
+    for day in &days {
+      exp.append_day(day).unwrap();
+    }
+    let mut act = TmeMli::new();
+    act.append_days_par(&days[..4]).unwrap();
+    act.append_days_par(&days[4..]).unwrap();
+    assert_eq!(exp.buf, act.buf);
+    act.verify().unwrap();
+
+    // Days out of order are an error and nothing is appended
+    let mut act = TmeMli::new();
+    act.append_days_par(&days[5..]).unwrap();
+    assert!(act.append_days_par(&days[..5]).is_err());
+    assert_eq!(days[5][0].date(), act.first_day().unwrap());
+    assert!(act.append_days_par(&[days[7].clone(), days[6].clone()]).is_err());
+  }
+
+  #[test]
+  fn decode_days_par_() {
+    let days = days(10);
+    let mut tme = TmeMli::new();
+    tme.append_days_par(&days).unwrap();
+    let unp = tme.decode_days_par(..);
+    assert_eq!(days.len(), unp.len());
+    for n in 0..days.len() {
+      assert_eq!(days[n][0].date(), unp.dates[n]);
+      assert_eq!(TmeMli::u32s(&days[n]), unp.day(n).to_vec());
+    }
+
+    let mut unp = DaysUnp::default();
+    tme.decode_days_par_into(days[2][0].date()..=days[4][0].date(), &mut unp);
+    assert_eq!(3, unp.len());
+    assert_eq!(TmeMli::u32s(&days[3]), unp.day(1).to_vec());
+    assert!(tme.decode_days_par(..days[0][0].date()).is_empty());
+  }
+}
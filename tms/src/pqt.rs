@@ -0,0 +1,113 @@
+//! `pqt` module imports and exports `TmeMli` days as Apache Parquet files.
+//!
+//! Files have the `arw` schema: a `Timestamp(Millisecond, "America/New_York")` column named `time`.
+//! By default the timestamp column is written uncompressed with Parquet's `DELTA_BINARY_PACKED` encoding,
+//! which makes file sizes directly comparable with `TmeMli` buffer sizes.
+
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use anyhow::{anyhow, Result};
+use chrono::NaiveDate;
+use parquet::arrow::ArrowWriter;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::basic::{Compression, Encoding};
+use parquet::file::properties::WriterProperties;
+use parquet::file::reader::ChunkReader;
+use parquet::schema::types::ColumnPath;
+use crate::TmeMli;
+use crate::arw;
+
+/// `props` returns the default writer properties: uncompressed, no dictionary, and delta encoded timestamps.
+pub fn props() -> WriterProperties {
+  return WriterProperties::builder()
+    .set_compression(Compression::UNCOMPRESSED)
+    .set_dictionary_enabled(false)
+    .set_column_encoding(ColumnPath::from(arw::TME_COL), Encoding::DELTA_BINARY_PACKED)
+    .build();
+}
+
+/// `write` writes days from `fst` to `lst` inclusive as Parquet with the default writer properties.
+pub fn write<W: Write + Send>(tme: &TmeMli, fst: NaiveDate, lst: NaiveDate, wtr: W) -> Result<()> {
+  return write_with(tme, fst, lst, wtr, props());
+}
+
+/// `write_with` writes days from `fst` to `lst` inclusive as Parquet with the specified writer properties.
+///
+/// Each trading day is a row group.
+pub fn write_with<W: Write + Send>(tme: &TmeMli, fst: NaiveDate, lst: NaiveDate, wtr: W, props: WriterProperties) -> Result<()> {
+  let mut wtr = ArrowWriter::try_new(wtr, arw::schema(), Some(props))?;
+  for (_, day) in tme.days().filter(|(date, _)| *date >= fst && *date <= lst) {
+    wtr.write(&arw::day_batch(&day))?;
+    wtr.flush()?;
+  }
+  wtr.close()?;
+  return Ok(());
+}
+
+/// `write_path` writes all days to a Parquet file.
+pub fn write_path<P: AsRef<Path>>(tme: &TmeMli, pth: P) -> Result<()> {
+  let fle = File::create(pth.as_ref())
+    .map_err(|e| anyhow!("{}: {}", pth.as_ref().display(), e))?;
+  let (fst, lst) = match (tme.first_day(), tme.last_day()) {
+    (Some(fst), Some(lst)) => (fst, lst),
+    _ => (NaiveDate::MIN, NaiveDate::MIN),
+  };
+  return write(tme, fst, lst, fle);
+}
+
+/// `read` reads a timestamp column of a multi-day Parquet file into a new `TmeMli`.
+///
+/// The column may have any timestamp unit and timezone. Times outside the trading session are dropped.
+pub fn read<R: ChunkReader + 'static>(rdr: R, col: &str) -> Result<TmeMli> {
+  let bats = ParquetRecordBatchReaderBuilder::try_new(rdr)?
+    .build()?
+    .collect::<std::result::Result<Vec<_>, _>>()?;
+  return arw::from_batches(&bats, col);
+}
+
+/// `read_path` reads a timestamp column of a multi-day Parquet file into a new `TmeMli`.
+pub fn read_path<P: AsRef<Path>>(pth: P, col: &str) -> Result<TmeMli> {
+  let fle = File::open(pth.as_ref())
+    .map_err(|e| anyhow!("{}: {}", pth.as_ref().display(), e))?;
+  return read(fle, col);
+}
+
+#[cfg(test)]
+mod tst {
+  use super::*;
+  use std::fs;
+  use chrono::{Duration, NaiveDateTime};
+
+  #[test]
+  fn write_read_path() {
+    let mut tme = TmeMli::new();
+    for date in [NaiveDate::from_ymd(2020, 5, 4), NaiveDate::from_ymd(2020, 5, 5), NaiveDate::from_ymd(2020, 5, 7)] {
+      let day: Vec<NaiveDateTime> = crate::dat::goog::day().iter()
+        .map(|v| date.and_hms(9, 30, 0) + Duration::milliseconds(*v as i64))
+        .collect();
+      tme.append_day(&day).unwrap();
+    }
+    let pth = std::env::temp_dir().join(format!("tms_pqt_{}.parquet", std::process::id()));
+    write_path(&tme, &pth).unwrap();
+    let act = read_path(&pth, arw::TME_COL).unwrap();
+    fs::remove_file(&pth).unwrap();
+    assert_eq!(tme.buf, act.buf);
+    assert!(read_path(&pth, arw::TME_COL).is_err());
+  }
+
+  #[test]
+  fn write_range() {
+    let mut tme = TmeMli::new();
+    let date1 = NaiveDate::from_ymd(2020, 5, 4);
+    let date2 = NaiveDate::from_ymd(2020, 5, 5);
+    tme.append_day(&[date1.and_hms(9, 30, 0)]).unwrap();
+    tme.append_day(&[date2.and_hms(9, 30, 0), date2.and_hms(10, 0, 0)]).unwrap();
+    let pth = std::env::temp_dir().join(format!("tms_pqt_rng_{}.parquet", std::process::id()));
+    write(&tme, date2, date2, File::create(&pth).unwrap()).unwrap();
+    let act = read_path(&pth, arw::TME_COL).unwrap();
+    fs::remove_file(&pth).unwrap();
+    assert_eq!(Some(date2), act.first_day());
+    assert_eq!(tme.get_day(date2), act.get_day(date2));
+  }
+}
@@ -0,0 +1,79 @@
+//! `scl` module provides scalar stand-ins for the AVX2 operations used by the generated functions.
+//!
+//! `scl` is compiled on targets other than x86_64, such as wasm32, so the core codec packs and unpacks the same bytes without SIMD.
+//!
+//! Each operation mirrors the AVX2 intrinsic it replaces, lane by lane, on eight u32 lanes.
+
+/// `m256` is a 256-bit vector of eight u32 lanes.
+#[allow(non_camel_case_types)]
+pub(crate) type m256 = [u32; 8];
+
+/// `load` reads a vector from a possibly unaligned pointer.
+#[inline]
+pub(crate) unsafe fn load(src: *const m256) -> m256 {
+  return core::ptr::read_unaligned(src);
+}
+
+/// `store` writes a vector to a possibly unaligned pointer.
+#[inline]
+pub(crate) unsafe fn store(dst: *mut m256, a: m256) {
+  core::ptr::write_unaligned(dst, a);
+}
+
+/// `set1` broadcasts a value to every lane.
+#[inline]
+pub(crate) fn set1(v: i32) -> m256 {
+  return [v as u32; 8];
+}
+
+/// `add` adds lanes, wrapping on overflow.
+#[inline]
+pub(crate) fn add(a: m256, b: m256) -> m256 {
+  return lns(a, b, u32::wrapping_add);
+}
+
+/// `sub` subtracts lanes, wrapping on overflow.
+#[inline]
+pub(crate) fn sub(a: m256, b: m256) -> m256 {
+  return lns(a, b, u32::wrapping_sub);
+}
+
+/// `and` bitwise ANDs lanes.
+#[inline]
+pub(crate) fn and(a: m256, b: m256) -> m256 {
+  return lns(a, b, |a, b| a & b);
+}
+
+/// `or` bitwise ORs lanes.
+#[inline]
+pub(crate) fn or(a: m256, b: m256) -> m256 {
+  return lns(a, b, |a, b| a | b);
+}
+
+/// `max_u32` selects the unsigned max of lanes.
+#[inline]
+pub(crate) fn max_u32(a: m256, b: m256) -> m256 {
+  return lns(a, b, core::cmp::max);
+}
+
+/// `lft` shifts lanes left, zeroing lanes when `shf` is 32 or more.
+#[inline]
+pub(crate) fn lft(a: m256, shf: i32) -> m256 {
+  return a.map(|a| a.checked_shl(shf as u32).unwrap_or(0));
+}
+
+/// `rht` shifts lanes right, zeroing lanes when `shf` is 32 or more.
+#[inline]
+pub(crate) fn rht(a: m256, shf: i32) -> m256 {
+  return a.map(|a| a.checked_shr(shf as u32).unwrap_or(0));
+}
+
+/// `lns` applies `f` to each pair of lanes.
+#[inline]
+fn lns(a: m256, b: m256, f: impl Fn(u32, u32) -> u32) -> m256 {
+  let mut out = a;
+  for n in 0..out.len() {
+    out[n] = f(a[n], b[n]);
+  }
+  return out;
+}
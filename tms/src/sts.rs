@@ -0,0 +1,209 @@
+//! `sts` module reports compression statistics per day and per block.
+//!
+//! Statistics are read from packed day headers without unpacking block values.
+
+use chrono::NaiveDate;
+use crate::{day_u32x256_hdr, DayRef, TmeMli};
+
+/// `LEN_NDT` is the number of bytes of a raw `NaiveDateTime`.
+pub const LEN_NDT: usize = 12;
+/// `LEN_I64` is the number of bytes of a raw i64 epoch timestamp.
+pub const LEN_I64: usize = 8;
+/// `LEN_BIT_HST` is the number of possible block bit-lengths, 0 through 32.
+pub const LEN_BIT_HST: usize = 33;
+
+/// `DayStats` are the compression statistics of a day.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DayStats {
+  pub date: NaiveDate,
+  /// `ticks` is the number of values.
+  pub ticks: usize,
+  /// `byt_len` is the number of packed bytes.
+  pub byt_len: usize,
+  /// `tal_byt_len` is the number of packed bytes in the varint or Stream VByte tail of less than 256 values.
+  pub tal_byt_len: usize,
+  /// `blk_bit_hst` counts blocks by bit-length.
+  pub blk_bit_hst: [usize; LEN_BIT_HST],
+}
+
+impl DayStats {
+  /// `new` returns the statistics of a stored day, or `None` when its headers are invalid.
+  pub fn new(day: &DayRef) -> Option<DayStats> {
+    let hdr = day_u32x256_hdr(day.pck)?;
+    let mut blk_bit_hst = [0usize; LEN_BIT_HST];
+    for bit_len in hdr.blk_bit_lens {
+      blk_bit_hst[bit_len as usize] += 1;
+    }
+    return Some(DayStats{
+      date: day.date,
+      ticks: hdr.cnt,
+      byt_len: hdr.byt_len,
+      tal_byt_len: hdr.tal_byt_len,
+      blk_bit_hst,
+    });
+  }
+
+  /// `blk_cnt` returns the number of binary packed blocks.
+  pub fn blk_cnt(&self) -> usize {
+    return self.blk_bit_hst.iter().sum();
+  }
+
+  /// `bits_per_tick` returns the packed bits per value.
+  pub fn bits_per_tick(&self) -> f64 {
+    return bits_per_tick(self.byt_len, self.ticks);
+  }
+
+  /// `tal_frc` returns the fraction of packed bytes in the tail.
+  pub fn tal_frc(&self) -> f64 {
+    return frc(self.tal_byt_len, self.byt_len);
+  }
+
+  /// `ratio_ndt` returns the compression ratio against raw 12-byte `NaiveDateTime`s.
+  pub fn ratio_ndt(&self) -> f64 {
+    return frc(self.ticks * LEN_NDT, self.byt_len);
+  }
+
+  /// `ratio_i64` returns the compression ratio against raw 8-byte i64s.
+  pub fn ratio_i64(&self) -> f64 {
+    return frc(self.ticks * LEN_I64, self.byt_len);
+  }
+}
+
+/// `Stats` are the compression statistics of a `TmeMli`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stats {
+  /// `days` are the statistics of each stored day in date order.
+  pub days: Vec<DayStats>,
+  /// `byt_len` is the number of bytes of the whole buffer, including the header and `Day Ref Idxs`.
+  pub byt_len: usize,
+}
+
+impl Stats {
+  /// `new` returns the statistics of each stored day. Days with invalid headers are skipped.
+  pub fn new(tme: &TmeMli) -> Stats {
+    return Stats{
+      days: tme.days().filter_map(|(_, day)| DayStats::new(&day)).collect(),
+      byt_len: tme.buf.len(),
+    };
+  }
+
+  /// `ticks` returns the number of values of all days.
+  pub fn ticks(&self) -> usize {
+    return self.days.iter().map(|day| day.ticks).sum();
+  }
+
+  /// `blk_bit_hst` counts blocks of all days by bit-length.
+  pub fn blk_bit_hst(&self) -> [usize; LEN_BIT_HST] {
+    let mut hst = [0usize; LEN_BIT_HST];
+    for day in &self.days {
+      for n in 0..LEN_BIT_HST {
+        hst[n] += day.blk_bit_hst[n];
+      }
+    }
+    return hst;
+  }
+
+  /// `bits_per_tick` returns the buffer bits per value.
+  pub fn bits_per_tick(&self) -> f64 {
+    return bits_per_tick(self.byt_len, self.ticks());
+  }
+
+  /// `tal_frc` returns the fraction of buffer bytes in tails.
+  pub fn tal_frc(&self) -> f64 {
+    return frc(self.days.iter().map(|day| day.tal_byt_len).sum(), self.byt_len);
+  }
+
+  /// `ratio_ndt` returns the compression ratio of the buffer against raw 12-byte `NaiveDateTime`s.
+  pub fn ratio_ndt(&self) -> f64 {
+    return frc(self.ticks() * LEN_NDT, self.byt_len);
+  }
+
+  /// `ratio_i64` returns the compression ratio of the buffer against raw 8-byte i64s.
+  pub fn ratio_i64(&self) -> f64 {
+    return frc(self.ticks() * LEN_I64, self.byt_len);
+  }
+}
+
+impl TmeMli {
+  /// `stats` returns the compression statistics of each stored day.
+  pub fn stats(&self) -> Stats {
+    return Stats::new(self);
+  }
+}
+
+/// `bits_per_tick` returns bits per value, or zero when there are no values.
+fn bits_per_tick(byt_len: usize, ticks: usize) -> f64 {
+  return frc(byt_len * 8, ticks);
+}
+
+/// `frc` returns `num / den`, or zero when `den` is zero.
+fn frc(num: usize, den: usize) -> f64 {
+  if den == 0 {
+    return 0.0;
+  }
+  return num as f64 / den as f64;
+}
+
+#[cfg(test)]
+mod tst {
+  use super::*;
+  use crate::tst::goog_tmes;
+  use crate::{day_u32x256_blks, u32x256_byt_len};
+
+  #[test]
+  fn stats_() {
+    let date1 = NaiveDate::from_ymd(2020, 5, 4);
+    let date2 = NaiveDate::from_ymd(2020, 5, 6);
+    let mut tme = TmeMli::new();
+    let day1 = goog_tmes(date1);
+    tme.append_day(&day1).unwrap();
+    tme.append_day(&goog_tmes(date2)[..100]).unwrap();
+    let sts = tme.stats();
+    assert_eq!(2, sts.days.len());
+    assert_eq!(tme.len_ticks(), sts.ticks());
+    assert_eq!(tme.buf.len(), sts.byt_len);
+
+    let meta = tme.day_meta(date1).unwrap();
+    let day = &sts.days[0];
+    assert_eq!(date1, day.date);
+    assert_eq!(day1.len(), day.ticks);
+    assert_eq!(meta.byt_len, day.byt_len);
+    assert_eq!(meta.blk_cnt(), day.blk_cnt());
+    for bit_len in meta.blk_bit_lens {
+      assert!(day.blk_bit_hst[bit_len as usize] != 0);
+    }
+    assert!(day.tal_byt_len > 0 && day.tal_byt_len < day.byt_len);
+    assert!(day.bits_per_tick() < 32.0);
+    assert!(day.ratio_ndt() > day.ratio_i64());
+
+    // Short days are only the varint tail and the counts
+    let day = &sts.days[1];
+    assert_eq!(0, day.blk_cnt());
+    assert_eq!(day.byt_len - 2, day.tal_byt_len);
+    assert_eq!(sts.blk_bit_hst(), sts.days[0].blk_bit_hst);
+    assert!(sts.tal_frc() > 0.0 && sts.tal_frc() < 1.0);
+    assert_eq!(Stats::new(&TmeMli::new()).bits_per_tick(), 0.0);
+  }
+
+  #[test]
+  fn stats_hdr_only() {
+    // Days of whole blocks are read from headers, so scrambled block values give the same statistics
+    let date = NaiveDate::from_ymd(2020, 5, 4);
+    let mut tme = TmeMli::new();
+    tme.append_day(&goog_tmes(date)[..512]).unwrap();
+    let day = tme.day(date).unwrap();
+    let mut pck = day.pck.to_vec();
+    let blks = day_u32x256_blks(&pck).unwrap();
+    for blk in &blks {
+      for idx in blk.idx..blk.idx + u32x256_byt_len(blk.bit_len) {
+        pck[idx] = !pck[idx];
+      }
+    }
+    let day_scr = DayStats::new(&DayRef{date, pck: &pck}).unwrap();
+    assert_eq!(DayStats::new(&day), Some(day_scr.clone()));
+    assert_eq!(512, day_scr.ticks);
+    assert_eq!(2, day_scr.blk_cnt());
+    assert_eq!(0, day_scr.tal_byt_len);
+    assert_eq!(pck.len(), day_scr.byt_len);
+  }
+}
@@ -181,6 +181,25 @@ pub fn  u32s_unp(src: &[u8], dst: &mut [u32]) {
   }
 }
 
+/// `u32s_lst` decodes the last u32 of variable length encoded bytes.
+/// 
+/// `src` is expected to end exactly at the last encoded byte.
+/// 
+/// The last value is found by scanning backward, so no other values are decoded.
+#[inline]
+pub fn u32s_lst(src: &[u8]) -> u32 {
+  if src.len() == 0 {
+    return 0;
+  }
+  // The last byte always has a header of 0
+  // Walk back while the previous byte indicates another byte exists
+  let mut s = src.len() - 1;
+  while s > 0 && src[s-1] & BYT_HDR_MSK != 0 {
+    s -= 1;
+  }
+  return u32_unp(&src[s..]).val;
+}
+
 #[cfg(test)]
 mod tst {
   use super::*;
@@ -440,6 +459,16 @@ mod tst {
     assert_eq!(11, u32s_byt_len(vec![0, 1, 128, 16384, 2097152].as_slice()));
   }
 
+  #[test]
+  fn u32s_lst_() {
+    assert_eq!(0, u32s_lst(&[]));
+    for unp in vec![vec![0], vec![1], vec![0, 128], vec![16384, 1], vec![0, 1, 128, 16384, 2097152], vec![2097152, u32::MAX]] {
+      let mut pck = vec![0; u32s_byt_len(&unp)];
+      u32s_pck(&unp, &mut pck);
+      assert_eq!(unp[unp.len()-1], u32s_lst(&pck));
+    }
+  }
+
   #[test]
   fn u32s_pck_unp_empty() {
     let unp_src = vec![];